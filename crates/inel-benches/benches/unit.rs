@@ -17,6 +17,46 @@ fn spawn_bench(c: &mut Criterion) {
     }
 }
 
+fn spawn_join(count: u32) {
+    inel::block_on(async move {
+        let handles = repeat_with(|| inel::spawn(async { black_box(1) + black_box(2) }))
+            .take(count as usize)
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            black_box(handle.join().await);
+        }
+    })
+}
+
+fn spawn_join_bench(c: &mut Criterion) {
+    for count in [1_000, 10_000, 100_000] {
+        c.bench_function(&format!("spawn join {count}"), |b| {
+            b.iter(|| spawn_join(black_box(count)))
+        });
+    }
+}
+
+fn wake(count: u32) {
+    for _ in 0..count {
+        inel::spawn(async {
+            for _ in 0..10 {
+                inel::task::yield_now().await;
+            }
+        });
+    }
+
+    inel::run();
+}
+
+fn wake_bench(c: &mut Criterion) {
+    for count in [1_000, 10_000] {
+        c.bench_function(&format!("wake {count}"), |b| {
+            b.iter(|| wake(black_box(count)))
+        });
+    }
+}
+
 fn submit(count: u32) {
     inel::block_on(async move {
         for _ in 0..count {
@@ -51,5 +91,12 @@ fn entries_bench(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, spawn_bench, submit_bench, entries_bench);
+criterion_group!(
+    benches,
+    spawn_bench,
+    spawn_join_bench,
+    wake_bench,
+    submit_bench,
+    entries_bench
+);
 criterion_main!(benches);
@@ -1,16 +1,43 @@
-use std::{future::Future, task::Context};
+use std::{cell::Cell, future::Future, rc::Rc, time::Duration};
 
-use futures::{channel::oneshot, FutureExt};
-use inel_interface::Reactor;
+use inel_interface::{coop, Reactor};
 use tracing::debug;
 
-use task::TaskQueue;
-use waker::waker;
-
-use crate::{join::JoinHandle, task, waker};
+use crate::{
+    hooks::Hooks,
+    join::{AbortHandle, JoinHandle},
+    queue::TaskQueue,
+    task::{Runnable, Task},
+    Priority,
+};
 
 pub struct Executor {
-    queue: TaskQueue,
+    queue: Rc<TaskQueue>,
+    hooks: Hooks,
+    spawned: Cell<u64>,
+    polls: Cell<u64>,
+}
+
+/// Snapshot of the executor counters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecutorMetrics {
+    /// Total number of spawned tasks
+    pub spawned: u64,
+    /// Total number of task polls
+    pub polls: u64,
+    /// Number of tasks that have not finished yet
+    pub tasks: usize,
+    /// Number of tasks scheduled to be polled
+    pub queued: usize,
+    /// Number of tasks scheduled to be polled, for each [Priority]
+    pub queued_by_priority: [usize; Priority::COUNT],
+}
+
+impl ExecutorMetrics {
+    /// Number of tasks of the given priority scheduled to be polled
+    pub fn queued_with(&self, priority: Priority) -> usize {
+        self.queued_by_priority[priority.index()]
+    }
 }
 
 impl Default for Executor {
@@ -22,7 +49,10 @@ impl Default for Executor {
 impl Executor {
     pub fn new() -> Self {
         Self {
-            queue: TaskQueue::new(),
+            queue: Rc::new(TaskQueue::new()),
+            hooks: Hooks::default(),
+            spawned: Cell::new(0),
+            polls: Cell::new(0),
         }
     }
 
@@ -30,17 +60,30 @@ impl Executor {
     where
         F: Future + 'static,
     {
-        let (sender, receiver) = oneshot::channel();
+        self.spawn_with_priority(Priority::Normal, future)
+    }
 
-        let task = future
-            .then(|res| async {
-                let _ = sender.send(res);
-            })
-            .fuse();
+    /// Spawns a task that is scheduled according to its [Priority]
+    pub fn spawn_with_priority<F>(&self, priority: Priority, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        let task = Task::new(&self.queue, priority, future);
+        task.schedule();
+        self.spawned.set(self.spawned.get() + 1);
 
-        self.queue.schedule(task);
+        let abort = Rc::downgrade(&task) as std::rc::Weak<dyn Runnable>;
+        JoinHandle::new(task, AbortHandle::new(abort))
+    }
 
-        JoinHandle::new(receiver)
+    pub fn metrics(&self) -> ExecutorMetrics {
+        ExecutorMetrics {
+            spawned: self.spawned.get(),
+            polls: self.polls.get(),
+            tasks: self.queue.tasks(),
+            queued: self.queue.len(),
+            queued_by_priority: Priority::ALL.map(|priority| self.queue.len_of(priority)),
+        }
     }
 
     pub fn block_on<R, F>(&self, reactor: R, future: F) -> F::Output
@@ -62,14 +105,114 @@ impl Executor {
         R: Reactor,
     {
         while !self.queue.is_done() {
-            debug!("Executing tasks");
-            for task in self.queue.drain() {
-                let waker = waker(task.clone());
-                let mut cx = Context::from_waker(&waker);
-                let _ = task.poll(&mut cx);
+            self.batch(&reactor);
+            self.idle_or_park(&reactor, None);
+        }
+    }
+
+    /// Polls the ready tasks and handles the completions that are already available,
+    /// until no task can make progress without blocking. Returns true if tasks are left.
+    pub fn run_until_stalled<R>(&self, reactor: R) -> bool
+    where
+        R: Reactor,
+    {
+        loop {
+            reactor.poll();
+
+            if self.queue.is_empty() {
+                self.hooks.idle();
             }
 
-            reactor.wait();
+            if self.queue.is_empty() {
+                return !self.queue.is_done();
+            }
+
+            self.batch(&reactor);
+        }
+    }
+
+    /// Handles completions, waiting up to `timeout` for them if no task is ready,
+    /// and then polls a single batch of ready tasks. Returns true if tasks are left.
+    pub fn turn<R>(&self, reactor: R, timeout: Duration) -> bool
+    where
+        R: Reactor,
+    {
+        self.idle_or_park(&reactor, Some(timeout));
+        self.batch(&reactor);
+
+        !self.queue.is_done()
+    }
+
+    /// Aborts every task that has not finished yet, their futures are dropped when they are
+    /// polled next. Returns the number of aborted tasks.
+    pub fn abort_all(&self) -> usize {
+        self.queue
+            .alive()
+            .into_iter()
+            .filter(|task| task.clone().abort())
+            .count()
+    }
+
+    /// Registers a callback that runs whenever there are no ready tasks, before parking.
+    /// Tasks spawned by it are polled before the executor parks.
+    pub fn on_idle<F>(&self, hook: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.hooks.add_idle(Box::new(hook));
+    }
+
+    /// Registers a callback that runs before blocking on the reactor
+    pub fn on_park<F>(&self, hook: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.hooks.add_park(Box::new(hook));
+    }
+
+    /// Registers a callback that runs after the reactor wakes up
+    pub fn on_unpark<F>(&self, hook: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.hooks.add_unpark(Box::new(hook));
+    }
+
+    /// Polls a weighted batch of the ready tasks
+    fn batch<R>(&self, reactor: &R)
+    where
+        R: Reactor,
+    {
+        debug!("Executing tasks");
+        for task in self.queue.drain() {
+            coop::budget(coop::DEFAULT_BUDGET, || task.run());
+            self.polls.set(self.polls.get() + 1);
+        }
+
+        reactor.tick();
+    }
+
+    /// Blocks on the reactor if there are no ready tasks, even after running the idle hooks
+    fn idle_or_park<R>(&self, reactor: &R, timeout: Option<Duration>)
+    where
+        R: Reactor,
+    {
+        if self.queue.is_empty() {
+            self.hooks.idle();
+        }
+
+        // tasks rescheduled during the last batch, for example after spending their budget,
+        // must not wait behind a blocking reactor
+        if !self.queue.is_empty() {
+            reactor.poll();
+            return;
+        }
+
+        self.hooks.park();
+        match timeout {
+            Some(timeout) => reactor.wait_timeout(timeout),
+            None => reactor.wait(),
         }
+        self.hooks.unpark();
     }
 }
@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+
+type Hook = Box<dyn Fn()>;
+
+/// Callbacks registered on an executor, see [Executor::on_idle](crate::Executor::on_idle)
+#[derive(Default)]
+pub(crate) struct Hooks {
+    idle: RefCell<Vec<Hook>>,
+    park: RefCell<Vec<Hook>>,
+    unpark: RefCell<Vec<Hook>>,
+}
+
+impl Hooks {
+    pub(crate) fn add_idle(&self, hook: Hook) {
+        self.idle.borrow_mut().push(hook);
+    }
+
+    pub(crate) fn add_park(&self, hook: Hook) {
+        self.park.borrow_mut().push(hook);
+    }
+
+    pub(crate) fn add_unpark(&self, hook: Hook) {
+        self.unpark.borrow_mut().push(hook);
+    }
+
+    pub(crate) fn idle(&self) {
+        Self::call(&self.idle);
+    }
+
+    pub(crate) fn park(&self) {
+        Self::call(&self.park);
+    }
+
+    pub(crate) fn unpark(&self) {
+        Self::call(&self.unpark);
+    }
+
+    /// Calls every hook of the list, which are allowed to register new ones
+    fn call(list: &RefCell<Vec<Hook>>) {
+        let hooks = list.take();
+        for hook in hooks.iter() {
+            hook();
+        }
+
+        let mut list = list.borrow_mut();
+        let added = std::mem::replace(&mut *list, hooks);
+        list.extend(added);
+    }
+}
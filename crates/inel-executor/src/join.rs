@@ -1,21 +1,61 @@
-use futures::channel::oneshot::Receiver;
+use std::{
+    future::poll_fn,
+    rc::{Rc, Weak},
+    task::Poll,
+};
+
+use crate::task::{Join, Runnable};
 
 pub struct JoinHandle<T> {
-    receiver: Receiver<T>,
+    task: Rc<dyn Join<T>>,
+    abort: AbortHandle,
 }
 
 impl<T> JoinHandle<T> {
-    pub(crate) fn new(receiver: Receiver<T>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(task: Rc<dyn Join<T>>, abort: AbortHandle) -> Self {
+        Self { task, abort }
     }
 
     pub fn try_join(&mut self) -> Option<T> {
-        self.receiver.try_recv().ok()?
+        match self.task.poll_join(None) {
+            Poll::Ready(output) => output,
+            Poll::Pending => None,
+        }
     }
 
+    /// Waits for the task to finish, returns `None` if it was aborted
     pub async fn join(self) -> Option<T> {
-        self.receiver.await.ok()
+        poll_fn(|cx| self.task.poll_join(Some(cx.waker()))).await
+    }
+
+    /// Stops the task before its next poll, dropping its future
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort.clone()
     }
 
     pub fn detach(self) {}
 }
+
+/// Aborts a task without holding on to its [JoinHandle]
+#[derive(Clone)]
+pub struct AbortHandle {
+    task: Weak<dyn Runnable>,
+}
+
+impl AbortHandle {
+    pub(crate) fn new(task: Weak<dyn Runnable>) -> Self {
+        Self { task }
+    }
+
+    /// Stops the task before its next poll, dropping its future.
+    /// Does nothing if the task already finished.
+    pub fn abort(&self) {
+        if let Some(task) = self.task.upgrade() {
+            task.abort();
+        }
+    }
+}
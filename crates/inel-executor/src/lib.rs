@@ -1,7 +1,13 @@
 mod executor;
+mod hooks;
 mod join;
+mod local;
+mod priority;
+mod queue;
 mod task;
 mod waker;
 
-pub use executor::Executor;
-pub use join::JoinHandle;
+pub use executor::{Executor, ExecutorMetrics};
+pub use join::{AbortHandle, JoinHandle};
+pub use local::{AccessError, LocalKey, Scope};
+pub use priority::Priority;
@@ -0,0 +1,204 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Display},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+/// Declares task local keys, with values that are set per task or for the duration of a scope.
+///
+/// ```ignore
+/// inel::task_local! {
+///     static REQUEST_ID: u64;
+/// }
+///
+/// REQUEST_ID.scope(42, async {
+///     assert_eq!(REQUEST_ID.get(), 42);
+/// }).await;
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::LocalKey<$ty> = $crate::LocalKey::new();
+        $crate::task_local!($($rest)*);
+    };
+}
+
+type Value = Rc<dyn Any>;
+
+/// Values of the task locals, swapped in while the task is polled
+#[derive(Default)]
+pub(crate) struct Locals {
+    values: HashMap<usize, Value>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Locals> = RefCell::new(Locals::default());
+}
+
+/// Installs the locals of a task on the current thread, until dropped
+pub(crate) struct Enter<'a> {
+    locals: &'a RefCell<Locals>,
+}
+
+impl<'a> Enter<'a> {
+    pub(crate) fn new(locals: &'a RefCell<Locals>) -> Self {
+        CURRENT.with_borrow_mut(|current| std::mem::swap(current, &mut locals.borrow_mut()));
+        Self { locals }
+    }
+}
+
+impl Drop for Enter<'_> {
+    fn drop(&mut self) {
+        CURRENT.with_borrow_mut(|current| std::mem::swap(current, &mut self.locals.borrow_mut()));
+    }
+}
+
+/// Returned when accessing a task local that is not set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessError;
+
+impl Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task local value not set")
+    }
+}
+
+impl std::error::Error for AccessError {}
+
+/// Key of a task local value, declared with [task_local]
+pub struct LocalKey<T> {
+    // makes every key a distinct address, which identifies its value
+    _unique: u8,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new() -> Self {
+        Self {
+            _unique: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    fn id(&'static self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Sets the value while the future is polled, it is only visible to that future
+    pub fn scope<F>(&'static self, value: T, future: F) -> Scope<T, F>
+    where
+        F: Future,
+    {
+        Scope {
+            key: self,
+            value: Some(Rc::new(value)),
+            future,
+        }
+    }
+
+    /// Sets the value while the closure runs
+    pub fn sync_scope<R>(&'static self, value: T, f: impl FnOnce() -> R) -> R {
+        let mut value = Some(Rc::new(value) as Value);
+        let _guard = Swap::new(self.id(), &mut value);
+        f()
+    }
+
+    /// Sets the value for the rest of the current task, returning the previous one
+    pub fn set(&'static self, value: T) -> Option<T> {
+        let previous =
+            CURRENT.with_borrow_mut(|current| current.values.insert(self.id(), Rc::new(value)));
+        previous.and_then(|previous| Rc::try_unwrap(previous.downcast().ok()?).ok())
+    }
+
+    /// Removes the value set with [LocalKey::set]
+    pub fn take(&'static self) -> Option<T> {
+        let previous = CURRENT.with_borrow_mut(|current| current.values.remove(&self.id()));
+        previous.and_then(|previous| Rc::try_unwrap(previous.downcast().ok()?).ok())
+    }
+
+    /// Runs the closure with a reference to the value.
+    ///
+    /// # Panics
+    /// If the value is not set in the current task.
+    pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        self.try_with(f).expect("task local value not set")
+    }
+
+    pub fn try_with<R>(&'static self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError> {
+        // the value is cloned out so the closure can access other task locals
+        let value = CURRENT.with_borrow(|current| current.values.get(&self.id()).cloned());
+        let value = value.ok_or(AccessError)?;
+        Ok(f(value
+            .downcast_ref()
+            .expect("task local of unexpected type")))
+    }
+}
+
+impl<T: Clone + 'static> LocalKey<T> {
+    /// Returns a copy of the value.
+    ///
+    /// # Panics
+    /// If the value is not set in the current task.
+    pub fn get(&'static self) -> T {
+        self.with(T::clone)
+    }
+}
+
+/// Swaps a value in the current locals, restoring the previous one when dropped
+struct Swap<'a> {
+    id: usize,
+    value: &'a mut Option<Value>,
+}
+
+impl<'a> Swap<'a> {
+    fn new(id: usize, value: &'a mut Option<Value>) -> Self {
+        CURRENT.with_borrow_mut(|current| replace(&mut current.values, id, value));
+        Self { id, value }
+    }
+}
+
+impl Drop for Swap<'_> {
+    fn drop(&mut self) {
+        CURRENT.with_borrow_mut(|current| replace(&mut current.values, self.id, self.value));
+    }
+}
+
+fn replace(values: &mut HashMap<usize, Value>, id: usize, value: &mut Option<Value>) {
+    let previous = match value.take() {
+        Some(value) => values.insert(id, value),
+        None => values.remove(&id),
+    };
+    *value = previous;
+}
+
+pin_project! {
+    /// Future returned by [LocalKey::scope]
+    pub struct Scope<T: 'static, F> {
+        key: &'static LocalKey<T>,
+        value: Option<Value>,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<T: 'static, F: Future> Future for Scope<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = Swap::new(this.key.id(), this.value);
+        this.future.poll(cx)
+    }
+}
@@ -0,0 +1,36 @@
+/// Scheduling class of a task.
+///
+/// Every executor batch polls up to [Priority::weight] times [QUANTUM] ready tasks of each
+/// class, in order, so higher classes get more polls but lower ones are never starved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Priority {
+    /// Latency critical tasks, like request handlers
+    High,
+    #[default]
+    Normal,
+    /// Tasks that can wait, like log shipping or compaction
+    Background,
+}
+
+/// Number of polls a class of weight 1 gets in every batch
+pub(crate) const QUANTUM: usize = 32;
+
+impl Priority {
+    pub const COUNT: usize = 3;
+
+    /// All the classes, from the highest to the lowest
+    pub const ALL: [Priority; Self::COUNT] = [Self::High, Self::Normal, Self::Background];
+
+    /// Share of the polls in a batch given to this class, relative to the others
+    pub const fn weight(self) -> usize {
+        match self {
+            Self::High => 4,
+            Self::Normal => 2,
+            Self::Background => 1,
+        }
+    }
+
+    pub(crate) const fn index(self) -> usize {
+        self as usize
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use slab::Slab;
+
+use crate::{
+    priority::{Priority, QUANTUM},
+    task::Runnable,
+};
+
+/// Intrusive FIFO of tasks, linked through their headers so pushing never allocates
+#[derive(Default)]
+struct List {
+    head: Cell<Option<Rc<dyn Runnable>>>,
+    tail: Cell<Option<Rc<dyn Runnable>>>,
+    len: Cell<usize>,
+}
+
+impl List {
+    fn push(&self, task: Rc<dyn Runnable>) {
+        match self.tail.replace(Some(task.clone())) {
+            Some(tail) => tail.header().next.set(Some(task)),
+            None => self.head.set(Some(task)),
+        }
+
+        self.len.set(self.len.get() + 1);
+    }
+
+    fn pop(&self) -> Option<Rc<dyn Runnable>> {
+        let task = self.head.take()?;
+
+        match task.header().next.take() {
+            Some(next) => self.head.set(Some(next)),
+            None => self.tail.set(None),
+        }
+
+        self.len.set(self.len.get() - 1);
+        Some(task)
+    }
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        // unlink one by one, dropping the head would recurse through the whole list
+        while self.pop().is_some() {}
+    }
+}
+
+/// Ready tasks, with a separate queue for every [Priority]
+#[derive(Default)]
+pub struct TaskQueue {
+    lists: [List; Priority::COUNT],
+    /// Every task that has not finished yet, so they can be aborted on shutdown
+    tasks: RefCell<Slab<Weak<dyn Runnable>>>,
+}
+
+impl TaskQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push(&self, priority: Priority, task: Rc<dyn Runnable>) {
+        self.lists[priority.index()].push(task);
+    }
+
+    /// Takes a weighted batch of the tasks scheduled so far, highest priority first.
+    /// The ones scheduled while polling them wait for the next batch.
+    pub(crate) fn drain(&self) -> impl Iterator<Item = Rc<dyn Runnable>> + '_ {
+        let counts =
+            Priority::ALL.map(|priority| self.len_of(priority).min(priority.weight() * QUANTUM));
+
+        self.lists
+            .iter()
+            .zip(counts)
+            .flat_map(|(list, count)| (0..count).map_while(|_| list.pop()))
+    }
+
+    pub(crate) fn start(&self, task: Weak<dyn Runnable>) -> usize {
+        self.tasks.borrow_mut().insert(task)
+    }
+
+    pub(crate) fn finish(&self, key: usize) {
+        self.tasks.borrow_mut().remove(key);
+    }
+
+    /// Tasks that have not finished yet
+    pub(crate) fn alive(&self) -> Vec<Rc<dyn Runnable>> {
+        self.tasks
+            .borrow()
+            .iter()
+            .filter_map(|(_, task)| task.upgrade())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lists.iter().map(|list| list.len.get()).sum()
+    }
+
+    pub fn len_of(&self, priority: Priority) -> usize {
+        self.lists[priority.index()].len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of tasks that are still running or referenced by wakers
+    pub fn tasks(&self) -> usize {
+        self.tasks.borrow().len()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.tasks() == 0
+    }
+}
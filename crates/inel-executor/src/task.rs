@@ -2,62 +2,224 @@ use std::{
     cell::{Cell, RefCell},
     future::Future,
     pin::Pin,
-    rc::Rc,
-    task::{Context, Poll},
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
 };
 
-use flume::{Receiver, Sender};
-
-type BoxFuture = Pin<Box<dyn Future<Output = ()>>>;
+use crate::{
+    local::{Enter, Locals},
+    queue::TaskQueue,
+    waker::waker,
+    Priority,
+};
 
-pub struct Task {
+/// Type erased part of a task, shared by all futures
+pub(crate) struct Header {
     scheduled: Cell<bool>,
-    future: RefCell<BoxFuture>,
-    queue: Sender<Rc<Task>>,
+    aborted: Cell<bool>,
+    /// Number of wakers and queue entries, plus one while the future is alive.
+    /// The task is finished when it drops to zero, so pending cancellations keep it around.
+    references: Cell<usize>,
+    /// Link to the next task in the run queue
+    pub(crate) next: Cell<Option<Rc<dyn Runnable>>>,
+    priority: Priority,
+    locals: RefCell<Locals>,
+    queue: Weak<TaskQueue>,
+    /// Key of the task in the queue registry
+    key: usize,
+}
+
+enum Stage<F: Future> {
+    Running(F),
+    Finished(F::Output),
+    Consumed,
+}
+
+/// A spawned future and its output, allocated once and shared by
+/// the run queue, the wakers and the [JoinHandle](crate::JoinHandle)
+pub(crate) struct Task<F: Future> {
+    header: Header,
+    stage: RefCell<Stage<F>>,
+    join: Cell<Option<Waker>>,
 }
 
-impl Task {
-    pub fn poll(&self, cx: &mut Context) -> Poll<()> {
-        self.scheduled.set(false);
-        self.future.borrow_mut().as_mut().poll(cx)
+/// Tasks that can be polled by the executor, regardless of their future
+pub(crate) trait Runnable {
+    fn header(&self) -> &Header;
+
+    /// Polls the task, consuming the reference taken out of the run queue
+    fn run(self: Rc<Self>);
+
+    /// Marks the task as aborted and schedules it, so its future is dropped when polled.
+    /// Returns false if the future was already dropped.
+    fn abort(self: Rc<Self>) -> bool;
+}
+
+/// Output side of a task
+pub(crate) trait Join<T> {
+    /// Takes the output of the task, registering the waker while it is running.
+    /// Returns `None` if the output was already taken or the task was aborted.
+    fn poll_join(&self, waker: Option<&Waker>) -> Poll<Option<T>>;
+}
+
+impl<F> Task<F>
+where
+    F: Future + 'static,
+{
+    pub(crate) fn new(queue: &Rc<TaskQueue>, priority: Priority, future: F) -> Rc<Self> {
+        Rc::new_cyclic(|task: &Weak<Self>| Self {
+            header: Header {
+                scheduled: Cell::new(false),
+                aborted: Cell::new(false),
+                references: Cell::new(1),
+                next: Cell::new(None),
+                priority,
+                locals: RefCell::new(Locals::default()),
+                queue: Rc::downgrade(queue),
+                key: queue.start(task.clone()),
+            },
+            stage: RefCell::new(Stage::Running(future)),
+            join: Cell::new(None),
+        })
     }
 
-    pub fn schedule(self: &Rc<Self>) {
-        if !self.scheduled.replace(true) {
-            self.queue.send(Rc::clone(self)).unwrap()
+    pub(crate) fn schedule(self: &Rc<Self>) {
+        if self.header.scheduled.replace(true) {
+            return;
+        }
+
+        match self.header.queue.upgrade() {
+            Some(queue) => {
+                self.acquire();
+                queue.push(self.header.priority, self.clone());
+            }
+            None => self.header.scheduled.set(false),
+        }
+    }
+
+    pub(crate) fn acquire(&self) {
+        let references = &self.header.references;
+        references.set(references.get() + 1);
+    }
+
+    /// Drops a reference, cancelling the future if nothing can wake it anymore
+    pub(crate) fn release(&self) {
+        let references = &self.header.references;
+        references.set(references.get() - 1);
+
+        match references.get() {
+            0 => {
+                if let Some(queue) = self.header.queue.upgrade() {
+                    queue.finish(self.header.key);
+                }
+            }
+            1 => {
+                if let Ok(mut stage) = self.stage.try_borrow_mut() {
+                    self.cancel(&mut stage);
+                }
+            }
+            _ => {}
         }
     }
-}
 
-pub struct TaskQueue {
-    sender: Sender<Rc<Task>>,
-    receiver: Receiver<Rc<Task>>,
+    fn poll(&self, cx: &mut Context) {
+        let _locals = Enter::new(&self.header.locals);
+        let mut stage = self.stage.borrow_mut();
+
+        if self.header.aborted.get() {
+            self.cancel(&mut stage);
+            return;
+        }
+
+        let Stage::Running(future) = &mut *stage else {
+            return;
+        };
+
+        // SAFETY: the future is never moved out of the task, which is kept in an `Rc`,
+        //         and it is dropped in place when the stage is replaced
+        let future = unsafe { Pin::new_unchecked(future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            *stage = Stage::Finished(output);
+            drop(stage);
+
+            self.release();
+            if let Some(waker) = self.join.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Drops the future if it is still running
+    fn cancel(&self, stage: &mut Stage<F>) {
+        if !matches!(stage, Stage::Running(_)) {
+            return;
+        }
+
+        *stage = Stage::Consumed;
+
+        self.release();
+        if let Some(waker) = self.join.take() {
+            waker.wake();
+        }
+    }
 }
 
-impl TaskQueue {
-    pub fn new() -> Self {
-        let (sender, receiver) = flume::unbounded();
-        Self { sender, receiver }
+impl<F> Runnable for Task<F>
+where
+    F: Future + 'static,
+{
+    fn header(&self) -> &Header {
+        &self.header
     }
 
-    pub fn schedule<F>(&self, future: F)
-    where
-        F: Future<Output = ()> + 'static,
-    {
-        let task = Rc::new(Task {
-            scheduled: Cell::new(false),
-            future: RefCell::new(Box::pin(future)),
-            queue: self.sender.clone(),
-        });
+    fn run(self: Rc<Self>) {
+        self.header.scheduled.set(false);
 
-        task.schedule();
+        // the waker takes over the queue entry
+        let waker = waker(self.clone());
+        let mut cx = Context::from_waker(&waker);
+        self.poll(&mut cx);
     }
 
-    pub fn drain(&self) -> impl Iterator<Item = Rc<Task>> + '_ {
-        self.receiver.try_iter()
+    fn abort(self: Rc<Self>) -> bool {
+        // a task polling itself is still running
+        let running = self
+            .stage
+            .try_borrow()
+            .map_or(true, |stage| matches!(*stage, Stage::Running(_)));
+
+        if running {
+            self.header.aborted.set(true);
+            self.schedule();
+        }
+
+        running
     }
+}
+
+impl<F> Join<F::Output> for Task<F>
+where
+    F: Future,
+{
+    fn poll_join(&self, waker: Option<&Waker>) -> Poll<Option<F::Output>> {
+        let mut stage = self.stage.borrow_mut();
+        match &*stage {
+            Stage::Running(_) => {
+                if let Some(waker) = waker {
+                    self.join.set(Some(waker.clone()));
+                }
+                Poll::Pending
+            }
 
-    pub fn is_done(&self) -> bool {
-        self.receiver.sender_count() == 1
+            Stage::Finished(_) => {
+                let Stage::Finished(output) = std::mem::replace(&mut *stage, Stage::Consumed)
+                else {
+                    unreachable!();
+                };
+                Poll::Ready(Some(output))
+            }
+
+            Stage::Consumed => Poll::Ready(None),
+        }
     }
 }
@@ -1,41 +1,53 @@
 use std::{
+    future::Future,
+    marker::PhantomData,
     rc::Rc,
     task::{RawWaker, RawWakerVTable, Waker},
 };
 
 use crate::task::Task;
 
-pub fn waker(task: Rc<Task>) -> Waker {
+/// Builds a waker that takes over one reference of the task, counted by [Task::acquire]
+pub fn waker<F>(task: Rc<Task<F>>) -> Waker
+where
+    F: Future + 'static,
+{
     let raw = Rc::into_raw(task).cast::<()>();
-    let vtable = &WakerHelper::VTABLE;
+    let vtable = &WakerHelper::<F>::VTABLE;
     unsafe { Waker::from_raw(RawWaker::new(raw, vtable)) }
 }
 
-struct WakerHelper;
+struct WakerHelper<F>(PhantomData<F>);
 
-impl WakerHelper {
+impl<F> WakerHelper<F>
+where
+    F: Future + 'static,
+{
     const VTABLE: RawWakerVTable =
         RawWakerVTable::new(Self::clone, Self::wake, Self::wake_by_ref, Self::drop);
 
     unsafe fn clone(ptr: *const ()) -> RawWaker {
-        let rc = Rc::from_raw(ptr.cast::<Task>());
+        let rc = Rc::from_raw(ptr.cast::<Task<F>>());
+        rc.acquire();
         std::mem::forget(rc.clone());
         std::mem::forget(rc);
         RawWaker::new(ptr, &Self::VTABLE)
     }
 
     unsafe fn wake(ptr: *const ()) {
-        let rc = Rc::from_raw(ptr.cast::<Task>());
+        let rc = Rc::from_raw(ptr.cast::<Task<F>>());
         rc.schedule();
+        rc.release();
     }
 
     unsafe fn wake_by_ref(ptr: *const ()) {
-        let rc = Rc::from_raw(ptr.cast::<Task>());
+        let rc = Rc::from_raw(ptr.cast::<Task<F>>());
         rc.schedule();
         std::mem::forget(rc);
     }
 
     unsafe fn drop(ptr: *const ()) {
-        drop(Rc::from_raw(ptr.cast::<Task>()));
+        let rc = Rc::from_raw(ptr.cast::<Task<F>>());
+        rc.release();
     }
 }
@@ -4,12 +4,13 @@ use std::{
     pin::Pin,
     rc::Rc,
     sync::{Arc, Once},
-    task::{Context, Poll, Waker},
+    task::{ready, Context, Poll, Waker},
+    time::Duration,
 };
 
 use futures::{FutureExt, SinkExt, StreamExt};
-use inel_executor::Executor;
-use inel_interface::Reactor;
+use inel_executor::{Executor, ExecutorMetrics, Priority};
+use inel_interface::{coop, Reactor};
 
 static TRACING: Once = Once::new();
 pub fn setup_tracing() {
@@ -344,3 +345,305 @@ fn everything() {
 
     assert_eq!(react.waited(), 101);
 }
+
+#[test]
+fn metrics() {
+    setup_tracing();
+    let exe = Executor::default();
+    let react = TestReactor::default();
+
+    assert_eq!(exe.metrics(), ExecutorMetrics::default());
+
+    for _ in 0..4 {
+        exe.spawn(Wait::new(2));
+    }
+
+    let metrics = exe.metrics();
+    assert_eq!(metrics.spawned, 4);
+    assert_eq!(metrics.tasks, 4);
+    assert_eq!(metrics.queued, 4);
+    assert_eq!(metrics.queued_with(Priority::Normal), 4);
+    assert_eq!(metrics.queued_with(Priority::High), 0);
+    assert_eq!(metrics.polls, 0);
+
+    exe.run(react);
+
+    let metrics = exe.metrics();
+    assert_eq!(metrics.spawned, 4);
+    assert_eq!(metrics.tasks, 0);
+    assert_eq!(metrics.queued, 0);
+    assert_eq!(metrics.polls, 12);
+}
+
+#[test]
+fn abort() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    struct Guard(Rc<Cell<bool>>);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    let dropped = Rc::new(Cell::new(false));
+    let (_sender, receiver) = futures::channel::oneshot::channel::<()>();
+
+    let guard = Guard(dropped.clone());
+    let handle = exe.spawn(async move {
+        let _guard = guard;
+        receiver.await.unwrap();
+    });
+
+    let abort = handle.abort_handle();
+    let res = exe.block_on(react, async move {
+        abort.abort();
+        handle.join().await
+    });
+
+    assert_eq!(res, None);
+    assert!(dropped.get());
+}
+
+#[test]
+fn abort_all() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (_sender, receiver) = futures::channel::oneshot::channel::<()>();
+    let pending = exe.spawn(async move {
+        receiver.await.unwrap();
+    });
+    let mut finished = exe.spawn(async {});
+
+    assert!(exe.run_until_stalled(react.clone()));
+    assert_eq!(exe.metrics().tasks, 1);
+
+    assert_eq!(exe.abort_all(), 1);
+    exe.run(react);
+
+    assert_eq!(exe.metrics().tasks, 0);
+    assert_eq!(finished.try_join(), Some(()));
+    assert!(pending.join().now_or_never().unwrap().is_none());
+}
+
+/// Always ready, but spends the budget of the task like a completed operation
+struct Ready;
+
+impl Future for Ready {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        ready!(coop::poll_proceed(cx));
+        coop::consume();
+        Poll::Ready(())
+    }
+}
+
+#[test]
+fn budget() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (steps, location) = output(Vec::new());
+
+    let hot = location.clone();
+    exe.spawn(async move {
+        for _ in 0..1000 {
+            Ready.await;
+        }
+        hot.borrow_mut().push("hot");
+    });
+
+    exe.spawn(async move {
+        location.borrow_mut().push("other");
+    });
+
+    exe.run(react);
+
+    assert_eq!(*steps.borrow(), vec!["other", "hot"]);
+    assert_eq!(exe.metrics().polls, 9);
+    assert_eq!(coop::remaining(), None);
+}
+
+#[test]
+fn priority() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (order, location) = output(Vec::new());
+
+    for priority in [Priority::Background, Priority::Normal, Priority::High] {
+        let location = location.clone();
+        exe.spawn_with_priority(priority, async move {
+            location.borrow_mut().push(priority);
+        });
+    }
+
+    let metrics = exe.metrics();
+    assert_eq!(metrics.queued_by_priority, [1, 1, 1]);
+
+    exe.run(react);
+
+    assert_eq!(
+        *order.borrow(),
+        vec![Priority::High, Priority::Normal, Priority::Background]
+    );
+}
+
+#[test]
+fn priority_starvation() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (finished, location) = output(0);
+
+    for _ in 0..1000 {
+        let location = location.clone();
+        exe.spawn_with_priority(Priority::High, async move {
+            Wait::new(10).await;
+            *location.borrow_mut() += 1;
+        });
+    }
+
+    let mut background = exe.spawn_with_priority(Priority::Background, async move {
+        Wait::new(10).await;
+        *finished.borrow()
+    });
+
+    exe.run(react);
+
+    // high priority tasks get more polls, but the background one is not starved
+    let high_finished = background.try_join().unwrap();
+    assert!(high_finished < 1000);
+}
+
+#[test]
+fn unreachable() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (dropped, location) = output(false);
+
+    struct Guard(Rc<RefCell<bool>>);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            *self.0.borrow_mut() = true;
+        }
+    }
+
+    // pending without keeping its waker, so it can never be polled again
+    let guard = Guard(location);
+    let mut handle = exe.spawn(async move {
+        let _guard = guard;
+        futures::future::pending::<()>().await;
+    });
+
+    exe.run(react);
+
+    assert!(*dropped.borrow());
+    assert_eq!(handle.try_join(), None);
+    assert_eq!(exe.metrics().tasks, 0);
+}
+
+#[test]
+fn drop_queued() {
+    setup_tracing();
+    let exe = Executor::new();
+
+    for _ in 0..100_000 {
+        exe.spawn(async {});
+    }
+
+    assert_eq!(exe.metrics().queued, 100_000);
+    drop(exe);
+}
+
+#[test]
+fn run_until_stalled() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let mut quick = exe.spawn(Wait::new(5));
+    let mut slow = exe.spawn(Wait::with_reactor(2, react.clone()));
+
+    // the slow task needs the reactor to wake it up, which never blocks here
+    assert!(exe.run_until_stalled(react.clone()));
+    assert_eq!(quick.try_join(), Some(()));
+    assert_eq!(slow.try_join(), None);
+    assert_eq!(react.waited(), 0);
+
+    let mut turns = 0;
+    while exe.turn(react.clone(), Duration::from_millis(1)) {
+        turns += 1;
+    }
+
+    assert_eq!(turns, 1);
+    assert_eq!(slow.try_join(), Some(()));
+    assert_eq!(react.waited(), 2);
+}
+
+#[test]
+fn hooks() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (events, location) = output(Vec::new());
+
+    let idle = location.clone();
+    exe.on_idle(move || idle.borrow_mut().push("idle"));
+    let park = location.clone();
+    exe.on_park(move || park.borrow_mut().push("park"));
+    let unpark = location.clone();
+    exe.on_unpark(move || unpark.borrow_mut().push("unpark"));
+
+    exe.block_on(react.clone(), Wait::with_reactor(1, react));
+
+    let events = events.borrow();
+    assert!(!events.is_empty());
+    assert!(events
+        .chunks(3)
+        .all(|events| events == ["idle", "park", "unpark"]));
+}
+
+inel_executor::task_local! {
+    static VALUE: u32;
+}
+
+#[test]
+fn task_local() {
+    setup_tracing();
+    let exe = Executor::new();
+    let react = TestReactor::default();
+
+    let (seen, out) = output(Vec::new());
+
+    for i in 0..3 {
+        let seen = seen.clone();
+        exe.spawn(VALUE.scope(i, async move {
+            Wait::new(i as usize + 1).await;
+            seen.borrow_mut().push(VALUE.get());
+        }));
+    }
+
+    let res = exe.block_on(react, async move {
+        assert!(VALUE.try_with(|_| ()).is_err());
+
+        assert_eq!(VALUE.set(7), None);
+        Wait::new(2).await;
+        VALUE.get()
+    });
+
+    assert_eq!(res, 7);
+    assert!(VALUE.try_with(|_| ()).is_err());
+    assert_eq!(*out.borrow(), vec![0, 1, 2]);
+}
@@ -0,0 +1,57 @@
+//! Cooperative scheduling budget.
+//!
+//! The executor gives every task poll a budget of ready operations. Once it is spent,
+//! futures that would complete without waiting return `Pending` and reschedule the task,
+//! so a task whose operations are always ready can not monopolise the thread.
+
+use std::{
+    cell::Cell,
+    task::{Context, Poll},
+};
+
+/// Number of ready operations a task can perform in a single poll
+pub const DEFAULT_BUDGET: u32 = 128;
+
+thread_local! {
+    static BUDGET: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// Runs the closure with a fresh budget, restoring the previous one afterwards
+pub fn budget<F, T>(budget: u32, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Reset(Option<u32>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            BUDGET.set(self.0);
+        }
+    }
+
+    let _reset = Reset(BUDGET.replace(Some(budget)));
+    f()
+}
+
+/// Returns `Pending` and wakes the task if its budget is spent.
+/// Outside of [budget] there is no limit.
+pub fn poll_proceed(cx: &mut Context<'_>) -> Poll<()> {
+    if BUDGET.get() == Some(0) {
+        cx.waker().wake_by_ref();
+        return Poll::Pending;
+    }
+
+    Poll::Ready(())
+}
+
+/// Spends one unit of the budget, should be called by futures that completed without waiting
+pub fn consume() {
+    if let Some(remaining) = BUDGET.get() {
+        BUDGET.set(Some(remaining.saturating_sub(1)));
+    }
+}
+
+/// Returns the remaining budget, or `None` if there is no limit
+pub fn remaining() -> Option<u32> {
+    BUDGET.get()
+}
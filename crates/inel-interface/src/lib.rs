@@ -1,4 +1,6 @@
-use std::future::Future;
+use std::{future::Future, time::Duration};
+
+pub mod coop;
 
 pub trait Executor {
     fn spawn<F>(&self, future: F)
@@ -19,7 +21,20 @@ pub trait Reactor {
     type Handle;
 
     fn wait(&self);
+
+    /// Like [Reactor::wait], but returns once the timeout expires even without completions
+    fn wait_timeout(&self, _timeout: Duration) {
+        self.wait();
+    }
+
     fn with<F, T>(&self, f: F) -> Option<T>
     where
         F: FnOnce(&mut Self::Handle) -> T;
+
+    /// Called by the executor after polling each batch of tasks
+    fn tick(&self) {}
+
+    /// Handles ready completions without blocking, called instead of [Reactor::wait]
+    /// when tasks are still scheduled
+    fn poll(&self) {}
 }
@@ -1,14 +1,141 @@
-use proc_macro::TokenStream;
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
 
 extern crate proc_macro;
 
+/// Splits the attribute arguments into `key = value` pairs, where a missing value means `true`
+fn parse_args(attr: TokenStream) -> Vec<(String, String)> {
+    let mut args = Vec::new();
+    let mut tokens = attr.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let TokenTree::Ident(key) = token else {
+            panic!("expected an option name, found `{token}`");
+        };
+
+        let mut value = String::from("true");
+        if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '=') {
+            tokens.next();
+
+            value.clear();
+            while let Some(token) = tokens.next_if(|t| !is_comma(t)) {
+                value.push_str(&token.to_string());
+            }
+        }
+
+        args.push((key.to_string(), value));
+
+        if let Some(token) = tokens.next() {
+            assert!(is_comma(&token), "expected `,`, found `{token}`");
+        }
+    }
+
+    args
+}
+
+fn is_comma(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Punct(p) if p.as_char() == ',')
+}
+
+/// Builds a `RingOptions` expression by calling a method for each option
+fn ring_options(args: &[(String, String)]) -> String {
+    args.iter().fold(
+        String::from("inel::RingOptions::default()"),
+        |options, (key, value)| format!("{options}.{key}({value})"),
+    )
+}
+
+/// Splits the outer attributes of a function, like `#[should_panic]`, from the rest of it
+fn split_attributes(item: TokenStream) -> (TokenStream, TokenStream) {
+    let mut tokens = item.into_iter().peekable();
+    let mut attrs = Vec::new();
+
+    while let Some(pound) =
+        tokens.next_if(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '#'))
+    {
+        attrs.push(pound);
+        attrs.extend(tokens.next());
+    }
+
+    (attrs.into_iter().collect(), tokens.collect())
+}
+
+/// Returns the name and the return type of an async function
+fn signature(item: &TokenStream) -> (String, String) {
+    let mut tokens = item.clone().into_iter();
+
+    tokens
+        .by_ref()
+        .find(|t| matches!(t, TokenTree::Ident(i) if i.to_string() == "fn"))
+        .expect("expected a function");
+
+    let name = tokens.next().expect("expected a function name").to_string();
+
+    let output = tokens
+        .skip_while(
+            |t| !matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis),
+        )
+        .skip(1)
+        .take_while(|t| !matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace))
+        .collect::<TokenStream>();
+
+    (name, output.to_string())
+}
+
+/// Runs an async main function, optionally configuring the reactor with `RingOptions`
+/// methods, for example `#[inel::main(submissions = 4096, fixed_buffers = 512, sqpoll)]`.
 #[proc_macro_attribute]
-pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    format!("fn main() {{ {item}; inel::block_on(main()) }}")
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_args(attr);
+
+    let init = if args.is_empty() {
+        String::new()
+    } else {
+        format!("inel::init({});", ring_options(&args))
+    };
+
+    format!("fn main() {{ {item}; {init} inel::block_on(main()) }}")
         .parse()
         .unwrap()
 }
 
+/// Runs an async test on a fresh runtime, asserting that no tasks, submissions or resources
+/// are leaked. Accepts the same options as [main], and a `timeout` in milliseconds.
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = parse_args(attr);
+    let (attrs, item) = split_attributes(item);
+    let (name, output) = signature(&item);
+
+    let timeout = args
+        .iter()
+        .position(|(key, _)| key == "timeout")
+        .map(|index| args.remove(index).1);
+
+    let options = ring_options(&args);
+
+    let body = match timeout {
+        Some(millis) => format!(
+            "let res = runtime.block_on(inel::time::timeout(\
+                std::time::Duration::from_millis({millis}), {name}()\
+            )).expect(\"Test timed out\");"
+        ),
+        None => format!("let res = runtime.block_on({name}());"),
+    };
+
+    format!(
+        "#[test] {attrs} fn {name}() {output} {{ \
+            {item}; \
+            let runtime = inel::Runtime::new({options}).expect(\"Failed to build runtime\"); \
+            {body} \
+            let report = runtime.shutdown(inel::SHUTDOWN_TIMEOUT); \
+            assert!(report.aborted == 0 && report.is_clean(), \"Test leaked tasks, submissions or resources: {{report:?}}\"); \
+            res \
+        }}"
+    )
+    .parse()
+    .unwrap()
+}
+
 #[proc_macro_attribute]
 pub fn test_repeat(attr: TokenStream, item: TokenStream) -> TokenStream {
     let name = item.clone().into_iter().nth(1).unwrap().to_string();
@@ -16,3 +143,267 @@ pub fn test_repeat(attr: TokenStream, item: TokenStream) -> TokenStream {
         .parse()
         .unwrap()
 }
+
+/// Splits a token stream on top level commas, ignoring a trailing one
+fn split_commas(input: TokenStream) -> Vec<TokenStream> {
+    let mut parts = vec![Vec::new()];
+    for token in input {
+        if is_comma(&token) {
+            parts.push(Vec::new());
+        } else {
+            parts.last_mut().unwrap().push(token);
+        }
+    }
+
+    parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| part.into_iter().collect())
+        .collect()
+}
+
+/// Polls all the futures concurrently, returning a tuple of their outputs,
+/// for example `let (a, b) = inel::join!(first(), second());`.
+#[proc_macro]
+pub fn join(input: TokenStream) -> TokenStream {
+    let futures = split_commas(input);
+
+    let pinned = futures
+        .iter()
+        .enumerate()
+        .map(|(i, fut)| {
+            format!("let mut __fut{i} = inel::__private::pin!(inel::__private::maybe_done({fut}));")
+        })
+        .collect::<String>();
+
+    let polls = (0..futures.len())
+        .map(|i| {
+            format!("__done &= ::core::future::Future::poll(__fut{i}.as_mut(), __cx).is_ready();")
+        })
+        .collect::<String>();
+
+    let outputs = (0..futures.len())
+        .map(|i| format!("__fut{i}.as_mut().take_output().unwrap(),"))
+        .collect::<String>();
+
+    format!(
+        "{{ \
+            {pinned} \
+            inel::__private::poll_fn(|__cx| {{ \
+                let mut __done = true; \
+                {polls} \
+                if !__done {{ return inel::__private::Poll::Pending; }} \
+                inel::__private::Poll::Ready(({outputs})) \
+            }}).await \
+        }}"
+    )
+    .parse()
+    .unwrap()
+}
+
+struct Branch {
+    pattern: String,
+    /// The pattern without binding modes, so it can check the output by reference
+    check: String,
+    future: String,
+    handler: String,
+}
+
+/// Removes the `mut` and `ref` binding modes from a pattern, keeping `&mut` reference patterns
+fn strip_binding_modes(pattern: TokenStream) -> TokenStream {
+    let mut stripped = Vec::new();
+    let mut reference = false;
+
+    for token in pattern {
+        let token = match token {
+            TokenTree::Ident(ident)
+                if !reference && matches!(ident.to_string().as_str(), "mut" | "ref") =>
+            {
+                continue;
+            }
+            TokenTree::Group(group) => {
+                let mut inner =
+                    proc_macro::Group::new(group.delimiter(), strip_binding_modes(group.stream()));
+                inner.set_span(group.span());
+                TokenTree::Group(inner)
+            }
+            token => token,
+        };
+
+        reference = matches!(&token, TokenTree::Punct(p) if p.as_char() == '&');
+        stripped.push(token);
+    }
+
+    stripped.into_iter().collect()
+}
+
+/// Parses the branches of [select], as `pattern = future => handler`,
+/// with an optional `else => handler` branch
+fn parse_branches(input: TokenStream) -> (Vec<Branch>, Option<String>) {
+    let mut tokens = input.into_iter().peekable();
+    let mut branches = Vec::new();
+    let mut fallback = None;
+
+    let is_arrow = |a: &TokenTree, b: Option<&TokenTree>| {
+        matches!(a, TokenTree::Punct(p) if p.as_char() == '=' && p.spacing() == Spacing::Joint)
+            && matches!(b, Some(TokenTree::Punct(p)) if p.as_char() == '>')
+    };
+
+    while tokens.peek().is_some() {
+        let mut pattern = Vec::new();
+        let mut previous_joint = false;
+        loop {
+            let token = tokens
+                .next()
+                .expect("expected `pattern = future => handler`");
+            if matches!(&token, TokenTree::Ident(i) if i.to_string() == "else")
+                && pattern.is_empty()
+            {
+                break;
+            }
+
+            if let TokenTree::Punct(p) = &token {
+                if p.as_char() == '=' && p.spacing() == Spacing::Alone && !previous_joint {
+                    break;
+                }
+                previous_joint = p.spacing() == Spacing::Joint;
+            } else {
+                previous_joint = false;
+            }
+
+            pattern.push(token);
+        }
+
+        let mut future = Vec::new();
+        while let Some(token) = tokens.next() {
+            if is_arrow(&token, tokens.peek()) {
+                tokens.next();
+                break;
+            }
+            future.push(token);
+        }
+
+        let handler = match tokens
+            .next_if(|t| matches!(t, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace))
+        {
+            Some(block) => {
+                tokens.next_if(is_comma);
+                block.to_string()
+            }
+            None => {
+                let mut handler = Vec::new();
+                while let Some(token) = tokens.next_if(|t| !is_comma(t)) {
+                    handler.push(token);
+                }
+                tokens.next();
+                handler.into_iter().collect::<TokenStream>().to_string()
+            }
+        };
+
+        if pattern.is_empty() {
+            assert!(future.is_empty(), "expected `else => handler`");
+            fallback = Some(handler);
+            continue;
+        }
+
+        let pattern = pattern.into_iter().collect::<TokenStream>();
+        branches.push(Branch {
+            check: strip_binding_modes(pattern.clone()).to_string(),
+            pattern: pattern.to_string(),
+            future: future.into_iter().collect::<TokenStream>().to_string(),
+            handler,
+        });
+    }
+
+    (branches, fallback)
+}
+
+/// Waits on multiple futures, running the handler of the first one to complete with
+/// an output matching its pattern. Branches are polled in order.
+///
+/// ```ignore
+/// inel::select! {
+///     Some(line) = lines.next() => println!("{line}"),
+///     _ = inel::time::sleep(timeout) => return,
+///     else => println!("all branches are disabled"),
+/// }
+/// ```
+///
+/// The futures are owned by the macro and all of them are dropped before the handler runs,
+/// so the operations still in flight are cancelled and their buffers released right away.
+/// A branch whose output does not match its pattern is disabled, and when all of them are,
+/// the `else` handler runs, or the macro panics if there is none.
+#[proc_macro]
+pub fn select(input: TokenStream) -> TokenStream {
+    let (branches, fallback) = parse_branches(input);
+    let count = branches.len();
+
+    let params = (0..count).map(|i| format!("T{i},")).collect::<String>();
+    let variants = (0..count)
+        .map(|i| format!("B{i}(T{i}),"))
+        .collect::<String>();
+
+    let pinned = branches
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            format!(
+                "let mut __fut{i} = inel::__private::pin!(::core::future::IntoFuture::into_future({}));",
+                b.future
+            )
+        })
+        .collect::<String>();
+
+    let polls = branches
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            format!(
+                "if !__disabled[{i}] {{ \
+                    if let inel::__private::Poll::Ready(__out) = ::core::future::Future::poll(__fut{i}.as_mut(), __cx) {{ \
+                        if matches!(&__out, {check}) {{ \
+                            return inel::__private::Poll::Ready(Some(__Branch::B{i}(__out))); \
+                        }} \
+                        __disabled[{i}] = true; \
+                    }} \
+                }}",
+                check = b.check
+            )
+        })
+        .collect::<String>();
+
+    let arms = branches
+        .iter()
+        .enumerate()
+        .map(|(i, b)| format!("Some(__Branch::B{i}({})) => {},", b.pattern, b.handler))
+        .collect::<String>();
+
+    let fallback = fallback
+        .unwrap_or_else(|| String::from("panic!(\"all branches of select! are disabled\")"));
+
+    format!(
+        "{{ \
+            #[allow(dead_code)] \
+            enum __Branch<{params}> {{ {variants} }} \
+            #[allow(unused_variables, unreachable_patterns, clippy::redundant_pattern_matching)] \
+            let __branch = {{ \
+                {pinned} \
+                let mut __disabled = [false; {count}]; \
+                inel::__private::poll_fn(|__cx| {{ \
+                    {polls} \
+                    if __disabled.iter().all(|disabled| *disabled) {{ \
+                        return inel::__private::Poll::Ready(None); \
+                    }} \
+                    inel::__private::Poll::Pending \
+                }}).await \
+            }}; \
+            #[allow(unreachable_patterns)] \
+            match __branch {{ \
+                {arms} \
+                _ => {fallback}, \
+            }} \
+        }}"
+    )
+    .parse()
+    .unwrap()
+}
@@ -1,4 +1,8 @@
-use std::{ffi::CString, rc::Rc};
+use std::{
+    ffi::CString,
+    rc::Rc,
+    sync::{atomic::AtomicU32, Arc},
+};
 
 use crate::ring::RingResult;
 
@@ -152,6 +156,19 @@ impl<T> From<Option<Box<T>>> for Cancellation {
     }
 }
 
+impl<T> From<Arc<T>> for Cancellation {
+    fn from(value: Arc<T>) -> Self {
+        Self {
+            ptr: Arc::into_raw(value) as *mut (),
+            metadata: 0,
+            drop: Some(|ptr, _| unsafe {
+                drop(Arc::from_raw(ptr as *const T));
+            }),
+            consume: None,
+        }
+    }
+}
+
 impl<T> From<Box<[T]>> for Cancellation {
     fn from(value: Box<[T]>) -> Self {
         let len = value.len();
@@ -211,3 +228,9 @@ impl From<&'static str> for Cancellation {
         Self::empty()
     }
 }
+
+impl From<&'static AtomicU32> for Cancellation {
+    fn from(_: &'static AtomicU32) -> Self {
+        Self::empty()
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    io::Result,
+    sync::{atomic::AtomicU32, Arc},
+};
+
+use io_uring::{opcode, squeue::Entry, types};
+
+use crate::{
+    cancellation::Cancellation,
+    op::{util, Op},
+    ring::RingResult,
+};
+
+/// `futex2` flag for 32 bit futex words
+const FUTEX2_SIZE_U32: u32 = 0x02;
+
+/// `futex2` flag for futexes that are not shared with other processes
+const FUTEX2_PRIVATE: u32 = 128;
+
+/// A futex word with a stable address, kept alive until the operation using it completes
+pub trait StableFutex: Into<Cancellation> {
+    fn futex(&self) -> &AtomicU32;
+}
+
+impl StableFutex for Arc<AtomicU32> {
+    fn futex(&self) -> &AtomicU32 {
+        self
+    }
+}
+
+impl StableFutex for &'static AtomicU32 {
+    fn futex(&self) -> &AtomicU32 {
+        self
+    }
+}
+
+/// Waits for a wake on a futex, if it still holds the expected value when submitted.
+/// Completes with `EAGAIN` if the value is different.
+pub struct FutexWait<F> {
+    futex: F,
+    expected: u32,
+    mask: u32,
+    flags: u32,
+}
+
+impl<F> FutexWait<F>
+where
+    F: StableFutex,
+{
+    pub fn new(futex: F, expected: u32) -> Self {
+        Self {
+            futex,
+            expected,
+            mask: u32::MAX,
+            flags: FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+        }
+    }
+
+    /// Only wakes matching a bit of the mask will complete the wait
+    pub fn mask(mut self, mask: u32) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Allows waking from other processes, for futexes in shared memory
+    pub fn shared(mut self) -> Self {
+        self.flags &= !FUTEX2_PRIVATE;
+        self
+    }
+}
+
+unsafe impl<F> Op for FutexWait<F>
+where
+    F: StableFutex,
+{
+    type Output = Result<()>;
+
+    fn entry(&mut self) -> Entry {
+        opcode::FutexWait::new(
+            self.futex.futex().as_ptr(),
+            self.expected as u64,
+            self.mask as u64,
+            self.flags,
+        )
+        .build()
+    }
+
+    fn result(self, res: RingResult) -> Self::Output {
+        util::expect_zero(&res)
+    }
+
+    fn cancel(self) -> Cancellation {
+        self.futex.into()
+    }
+}
+
+/// Wakes up to `count` waiters of a futex, completes with the number of woken waiters
+pub struct FutexWake<F> {
+    futex: F,
+    count: u32,
+    mask: u32,
+    flags: u32,
+}
+
+impl<F> FutexWake<F>
+where
+    F: StableFutex,
+{
+    pub fn new(futex: F, count: u32) -> Self {
+        Self {
+            futex,
+            count,
+            mask: u32::MAX,
+            flags: FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+        }
+    }
+
+    /// Only wakes waiters whose mask matches a bit of this one
+    pub fn mask(mut self, mask: u32) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Wakes waiters from other processes, for futexes in shared memory
+    pub fn shared(mut self) -> Self {
+        self.flags &= !FUTEX2_PRIVATE;
+        self
+    }
+}
+
+unsafe impl<F> Op for FutexWake<F>
+where
+    F: StableFutex,
+{
+    type Output = Result<usize>;
+
+    fn entry(&mut self) -> Entry {
+        opcode::FutexWake::new(
+            self.futex.futex().as_ptr(),
+            self.count as u64,
+            self.mask as u64,
+            self.flags,
+        )
+        .build()
+    }
+
+    fn result(self, res: RingResult) -> Self::Output {
+        util::expect_positive(&res)
+    }
+
+    fn cancel(self) -> Cancellation {
+        self.futex.into()
+    }
+}
+
+/// Waits for a wake on any of the futexes, if all of them hold their expected values
+/// when submitted. Completes with the index of the woken futex.
+pub struct FutexWaitV<F> {
+    futexes: Vec<F>,
+    waitv: Box<[types::FutexWaitV]>,
+}
+
+impl<F> FutexWaitV<F>
+where
+    F: StableFutex,
+{
+    /// Takes pairs of futexes and their expected values, up to 128
+    pub fn new(futexes: impl IntoIterator<Item = (F, u32)>) -> Self {
+        let (futexes, waitv): (Vec<_>, Vec<_>) = futexes
+            .into_iter()
+            .map(|(futex, expected)| {
+                let waitv = types::FutexWaitV::new()
+                    .uaddr(futex.futex().as_ptr() as u64)
+                    .val(expected as u64)
+                    .flags(FUTEX2_SIZE_U32 | FUTEX2_PRIVATE);
+
+                (futex, waitv)
+            })
+            .unzip();
+
+        Self {
+            futexes,
+            waitv: waitv.into_boxed_slice(),
+        }
+    }
+}
+
+unsafe impl<F> Op for FutexWaitV<F>
+where
+    F: StableFutex,
+{
+    type Output = Result<usize>;
+
+    fn entry(&mut self) -> Entry {
+        opcode::FutexWaitV::new(self.waitv.as_ptr(), self.waitv.len() as u32).build()
+    }
+
+    fn result(self, res: RingResult) -> Self::Output {
+        util::expect_positive(&res)
+    }
+
+    fn cancel(self) -> Cancellation {
+        let mut cancels = vec![self.waitv.into()];
+        cancels.extend(self.futexes.into_iter().map(Into::into));
+        Cancellation::combine(cancels)
+    }
+}
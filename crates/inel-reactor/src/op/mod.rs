@@ -1,5 +1,6 @@
 mod direct;
 mod file;
+mod futex;
 mod group;
 mod link;
 mod net;
@@ -23,6 +24,7 @@ use crate::{
 
 pub use direct::*;
 pub use file::*;
+pub use futex::*;
 pub use group::*;
 pub use link::*;
 pub use net::*;
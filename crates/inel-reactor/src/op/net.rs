@@ -1,19 +1,23 @@
 use std::{
+    collections::VecDeque,
     io::Result,
     mem::{self, MaybeUninit},
     net::SocketAddr,
     os::fd::RawFd,
     ptr::addr_of_mut,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use io_uring::{opcode, squeue::Entry, types::DestinationSlot};
 
 use crate::{
-    cancellation::Cancellation,
+    cancellation::{consuming, Cancellation},
     op::{util, DetachOp, MultiOp, Op},
     ring::{DirectSlot, RingResult},
     source::{AsDirectSlot, AsSource, DirectAutoFd, Source},
-    util::{from_raw_addr, into_raw_addr, SocketAddrCRepr},
+    util::{from_raw_addr, into_raw_addr, setsockopt, SocketAddrCRepr},
 };
 
 pub struct Socket {
@@ -377,19 +381,77 @@ unsafe impl Op for Shutdown {
 
 impl DetachOp for Shutdown {}
 
+/// Collects connections accepted by a multishot accept after it has been cancelled,
+/// so they can be picked up later instead of being lost
+#[derive(Clone, Default)]
+pub struct AcceptBacklog {
+    results: Arc<Mutex<VecDeque<i32>>>,
+}
+
+impl AcceptBacklog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn pop_fd(&self) -> Option<RawFd> {
+        self.results.lock().unwrap().pop_front()
+    }
+
+    pub fn pop_direct(&self) -> Option<DirectAutoFd> {
+        self.pop_fd()
+            .map(|slot| DirectAutoFd::from_raw_slot(slot as u32))
+    }
+
+    fn cancellation(backlog: Option<Self>, close: fn(i32)) -> Cancellation {
+        let Some(backlog) = backlog else {
+            return consuming!(fn(i32), Rc::new(close), |close, res| {
+                if res.ret() > 0 {
+                    close(res.ret());
+                }
+            });
+        };
+
+        consuming!(AcceptBacklog, Rc::new(backlog), |backlog, res| {
+            if res.ret() > 0 {
+                backlog.results.lock().unwrap().push_back(res.ret());
+            }
+        })
+    }
+}
+
 pub struct AcceptMulti {
     src: Source,
+    backlog: Option<AcceptBacklog>,
 }
 
 impl AcceptMulti {
     pub fn new(source: &impl AsSource) -> Self {
         Self {
             src: source.as_source(),
+            backlog: None,
         }
     }
 
+    /// Sets a backlog for connections accepted after cancellation,
+    /// otherwise they will be closed
+    pub fn backlog(mut self, backlog: &AcceptBacklog) -> Self {
+        self.backlog = Some(backlog.clone());
+        self
+    }
+
     pub fn direct(self) -> AcceptMultiAuto {
-        AcceptMultiAuto { src: self.src }
+        AcceptMultiAuto {
+            src: self.src,
+            backlog: self.backlog,
+        }
     }
 }
 
@@ -403,6 +465,12 @@ unsafe impl Op for AcceptMulti {
     fn result(self, res: RingResult) -> Self::Output {
         self.next(res)
     }
+
+    fn cancel(self) -> Cancellation {
+        AcceptBacklog::cancellation(self.backlog, |fd| unsafe {
+            libc::close(fd);
+        })
+    }
 }
 
 impl MultiOp for AcceptMulti {
@@ -413,6 +481,16 @@ impl MultiOp for AcceptMulti {
 
 pub struct AcceptMultiAuto {
     src: Source,
+    backlog: Option<AcceptBacklog>,
+}
+
+impl AcceptMultiAuto {
+    /// Sets a backlog for connections accepted after cancellation,
+    /// otherwise their slots will be leaked
+    pub fn backlog(mut self, backlog: &AcceptBacklog) -> Self {
+        self.backlog = Some(backlog.clone());
+        self
+    }
 }
 
 unsafe impl Op for AcceptMultiAuto {
@@ -427,6 +505,10 @@ unsafe impl Op for AcceptMultiAuto {
     fn result(self, res: RingResult) -> Self::Output {
         self.next(res)
     }
+
+    fn cancel(self) -> Cancellation {
+        AcceptBacklog::cancellation(self.backlog, |_| {})
+    }
 }
 
 impl MultiOp for AcceptMultiAuto {
@@ -434,3 +516,178 @@ impl MultiOp for AcceptMultiAuto {
         util::expect_direct(&res)
     }
 }
+
+#[derive(Clone, Copy)]
+enum SockOptValue {
+    Int(libc::c_int),
+    Linger(libc::linger),
+}
+
+/// A typed socket option, that can be applied with [SetSockOpt] or [SockOpt::apply]
+#[derive(Clone, Copy)]
+pub struct SockOpt {
+    level: libc::c_int,
+    name: libc::c_int,
+    value: SockOptValue,
+}
+
+impl SockOpt {
+    fn int(level: libc::c_int, name: libc::c_int, value: libc::c_int) -> Self {
+        Self {
+            level,
+            name,
+            value: SockOptValue::Int(value),
+        }
+    }
+
+    fn flag(level: libc::c_int, name: libc::c_int, value: bool) -> Self {
+        Self::int(level, name, value as libc::c_int)
+    }
+
+    fn seconds(level: libc::c_int, name: libc::c_int, value: Duration) -> Self {
+        Self::int(
+            level,
+            name,
+            value.as_secs().clamp(1, i32::MAX as u64) as libc::c_int,
+        )
+    }
+
+    fn clamped(level: libc::c_int, name: libc::c_int, value: impl TryInto<libc::c_int>) -> Self {
+        Self::int(level, name, value.try_into().unwrap_or(libc::c_int::MAX))
+    }
+
+    /// `TCP_NODELAY`
+    pub fn nodelay(value: bool) -> Self {
+        Self::flag(libc::IPPROTO_TCP, libc::TCP_NODELAY, value)
+    }
+
+    /// `SO_KEEPALIVE`
+    pub fn keepalive(value: bool) -> Self {
+        Self::flag(libc::SOL_SOCKET, libc::SO_KEEPALIVE, value)
+    }
+
+    /// `TCP_KEEPIDLE`, rounded to seconds
+    pub fn keepalive_idle(value: Duration) -> Self {
+        Self::seconds(libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, value)
+    }
+
+    /// `TCP_KEEPINTVL`, rounded to seconds
+    pub fn keepalive_interval(value: Duration) -> Self {
+        Self::seconds(libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, value)
+    }
+
+    /// `TCP_KEEPCNT`
+    pub fn keepalive_count(value: u32) -> Self {
+        Self::clamped(libc::IPPROTO_TCP, libc::TCP_KEEPCNT, value)
+    }
+
+    /// `SO_LINGER`, rounded to seconds
+    pub fn linger(value: Option<Duration>) -> Self {
+        Self {
+            level: libc::SOL_SOCKET,
+            name: libc::SO_LINGER,
+            value: SockOptValue::Linger(libc::linger {
+                l_onoff: value.is_some() as libc::c_int,
+                l_linger: value.map_or(0, |d| d.as_secs().min(i32::MAX as u64) as libc::c_int),
+            }),
+        }
+    }
+
+    /// `SO_REUSEADDR`
+    pub fn reuseaddr(value: bool) -> Self {
+        Self::flag(libc::SOL_SOCKET, libc::SO_REUSEADDR, value)
+    }
+
+    /// `SO_REUSEPORT`
+    pub fn reuseport(value: bool) -> Self {
+        Self::flag(libc::SOL_SOCKET, libc::SO_REUSEPORT, value)
+    }
+
+    /// `SO_RCVBUF`
+    pub fn recv_buffer_size(value: usize) -> Self {
+        Self::clamped(libc::SOL_SOCKET, libc::SO_RCVBUF, value)
+    }
+
+    /// `SO_SNDBUF`
+    pub fn send_buffer_size(value: usize) -> Self {
+        Self::clamped(libc::SOL_SOCKET, libc::SO_SNDBUF, value)
+    }
+
+    /// `IP_TTL`
+    pub fn ttl(value: u32) -> Self {
+        Self::clamped(libc::IPPROTO_IP, libc::IP_TTL, value)
+    }
+
+    /// `TCP_FASTOPEN`, with the maximum length of pending syns
+    pub fn fastopen(queue: u32) -> Self {
+        Self::clamped(libc::IPPROTO_TCP, libc::TCP_FASTOPEN, queue)
+    }
+
+    /// `TCP_FASTOPEN_CONNECT`
+    pub fn fastopen_connect(value: bool) -> Self {
+        Self::flag(libc::IPPROTO_TCP, libc::TCP_FASTOPEN_CONNECT, value)
+    }
+
+    /// Applies the option to a raw socket with a blocking `setsockopt` syscall
+    pub fn apply(&self, sock: RawFd) -> Result<()> {
+        match &self.value {
+            SockOptValue::Int(value) => setsockopt(sock, self.level, self.name, value),
+            SockOptValue::Linger(value) => setsockopt(sock, self.level, self.name, value),
+        }
+    }
+
+    fn as_raw(&self) -> (*const libc::c_void, u32) {
+        match &self.value {
+            SockOptValue::Int(value) => (
+                value as *const _ as *const libc::c_void,
+                mem::size_of::<libc::c_int>() as u32,
+            ),
+            SockOptValue::Linger(value) => (
+                value as *const _ as *const libc::c_void,
+                mem::size_of::<libc::linger>() as u32,
+            ),
+        }
+    }
+}
+
+pub struct SetSockOpt {
+    src: Source,
+    opt: Box<SockOpt>,
+}
+
+impl SetSockOpt {
+    pub fn new(source: &impl AsSource, opt: SockOpt) -> Self {
+        Self {
+            src: source.as_source(),
+            opt: Box::new(opt),
+        }
+    }
+}
+
+unsafe impl Op for SetSockOpt {
+    type Output = Result<()>;
+
+    fn entry(&mut self) -> Entry {
+        let (value, len) = self.opt.as_raw();
+        opcode::SetSockOpt::new(
+            self.src.as_raw(),
+            self.opt.level as u32,
+            self.opt.name as u32,
+            value,
+            len,
+        )
+        .build()
+    }
+
+    fn result(self, res: RingResult) -> Self::Output {
+        util::expect_zero(&res)
+    }
+
+    fn cancel(self) -> Cancellation {
+        self.opt.into()
+    }
+
+    fn entry_cancel(_key: u64) -> Option<Entry> {
+        None
+    }
+}
@@ -215,7 +215,12 @@ impl Completion {
 
             Completion::Cancelled { cancel } => {
                 cancel.consume(result);
-                cancel.drop_raw();
+
+                if result.has_more() {
+                    *self = Completion::Cancelled { cancel };
+                } else {
+                    cancel.drop_raw();
+                }
             }
 
             _ => {
@@ -336,6 +341,7 @@ mod test {
                 next.push(case.clone_extend(&[C::Result]));
 
                 cases.push(case.clone_extend(&[C::Cancel, C::NotifySingle]));
+                cases.push(case.clone_extend(&[C::Cancel, C::NotifyMulti, C::NotifySingle]));
                 cases.push(case.clone_extend(&[C::NotifySingle, C::Cancel]));
                 cases.push(case.clone_extend(&[C::NotifySingle]).fix());
             }
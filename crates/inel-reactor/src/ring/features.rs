@@ -0,0 +1,56 @@
+use io_uring::{opcode, IoUring, Probe};
+use tracing::warn;
+
+/// Opcodes supported by the running kernel, as reported by `IORING_REGISTER_PROBE`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Features {
+    opcodes: [u64; 4],
+}
+
+impl Features {
+    pub(crate) fn probe(ring: &IoUring) -> Self {
+        let mut probe = Probe::new();
+        if let Err(err) = ring.submitter().register_probe(&mut probe) {
+            warn!(?err, "failed to probe supported opcodes");
+            return Self::default();
+        }
+
+        let mut features = Self::default();
+        for opcode in 0..=u8::MAX {
+            if probe.is_supported(opcode) {
+                features.opcodes[opcode as usize / 64] |= 1 << (opcode % 64);
+            }
+        }
+
+        features
+    }
+
+    /// Returns true if the opcode is supported, for example `io_uring::opcode::Nop::CODE`.
+    /// Always false when probing is not available (before linux 5.6).
+    pub fn supports(&self, opcode: u8) -> bool {
+        self.opcodes[opcode as usize / 64] & (1 << (opcode % 64)) != 0
+    }
+
+    /// Returns true if accept can be multishot.
+    ///
+    /// NOTE: multishot accept shares the opcode of accept, so this checks for
+    ///       `IORING_OP_SOCKET`, which was introduced in the same release (linux 5.19)
+    pub fn multishot_accept(&self) -> bool {
+        self.supports(opcode::Accept::CODE) && self.supports(opcode::Socket::CODE)
+    }
+
+    /// Returns true if socket options can be set through the ring.
+    ///
+    /// NOTE: this checks for `IORING_OP_URING_CMD` (linux 5.19), sockets only support
+    ///       the command since linux 6.7 and fail with `EOPNOTSUPP` before
+    pub fn setsockopt(&self) -> bool {
+        self.supports(opcode::SetSockOpt::CODE)
+    }
+
+    /// Returns true if futex operations are supported (linux 6.7)
+    pub fn futex(&self) -> bool {
+        self.supports(opcode::FutexWait::CODE)
+            && self.supports(opcode::FutexWake::CODE)
+            && self.supports(opcode::FutexWaitV::CODE)
+    }
+}
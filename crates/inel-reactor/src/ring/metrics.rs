@@ -0,0 +1,53 @@
+/// Occupancy of a registered resource table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlotUsage {
+    /// Number of slots currently in use
+    pub used: u32,
+    /// Total number of slots
+    pub capacity: u32,
+}
+
+/// Snapshot of the counters and queue depths of a [Ring](super::Ring)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RingMetrics {
+    /// Total number of entries submitted, including detached and cancel entries
+    pub submitted: u64,
+    /// Total number of times queued entries were submitted without waiting for completions
+    pub flushes: u64,
+    /// Total number of completions handled
+    pub completed: u64,
+    /// Total number of cancelled submissions
+    pub cancelled: u64,
+    /// Number of submissions waiting for completions
+    pub active: u32,
+    /// Number of detached entries waiting for completions
+    pub detached: u32,
+    /// Number of entries queued, but not yet submitted to the kernel
+    pub sq_depth: usize,
+    /// Number of completions posted, but not yet handled
+    pub cq_depth: usize,
+    /// Number of entries waiting for room in the submission queue
+    pub backlogged: usize,
+    /// Total number of entries that did not fit in the submission queue
+    pub sq_overflow: u64,
+    /// Total number of times the completion queue overflowed
+    pub cq_overflow: u64,
+    /// Total number of completions dropped by the kernel
+    pub cq_dropped: u32,
+    /// Fixed buffer slots
+    pub fixed_buffers: SlotUsage,
+    /// Manual direct file descriptor slots
+    pub direct_files: SlotUsage,
+    /// Buffer group ids
+    pub buffer_groups: SlotUsage,
+}
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    pub(crate) submitted: u64,
+    pub(crate) flushes: u64,
+    pub(crate) completed: u64,
+    pub(crate) cancelled: u64,
+    pub(crate) sq_overflow: u64,
+    pub(crate) cq_overflow: u64,
+}
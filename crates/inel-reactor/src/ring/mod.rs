@@ -1,23 +1,39 @@
 mod completion;
+mod features;
+mod metrics;
 mod register;
 
-use std::io::Error;
+use std::collections::VecDeque;
 use std::io::Result;
+use std::io::{Error, ErrorKind};
+use std::os::fd::{AsRawFd, RawFd};
 use std::task::Waker;
+use std::time::{Duration, Instant};
 
-use io_uring::{cqueue, squeue::Entry, IoUring};
-use tracing::{debug, warn};
+use io_uring::types::{SubmitArgs, Timespec};
+use io_uring::{
+    cqueue,
+    squeue::{Entry, Flags},
+    IoUring, Parameters,
+};
+use tracing::{debug, error, warn};
 
 use crate::{buffer::StableBuffer, cancellation::Cancellation};
 
 use completion::CompletionSet;
+use metrics::Counters;
 use register::SlotRegister;
 
 pub use completion::Key;
+pub use features::Features;
+pub use metrics::{RingMetrics, SlotUsage};
 pub use register::{BufferGroupId, BufferSlot, DirectSlot};
 
 const IGNORE_KEY: u64 = u64::MAX - 1;
 
+/// Flag of `io_uring_enter` asking for completions, not exported by [io_uring]
+const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+
 #[derive(Clone, Copy, Debug)]
 pub struct RingResult {
     ret: i32,
@@ -49,23 +65,66 @@ impl RingResult {
     }
 }
 
-/// Options for resource allocation
+/// Options for resource allocation and ring setup
 pub struct RingOptions {
     submissions: u32,
+    completions: Option<u32>,
     fixed_buffers: u32,
     buffer_groups: u32,
     auto_direct_files: u32,
     manual_direct_files: u32,
+    sqpoll: bool,
+    sq_thread_idle: u32,
+    sq_thread_cpu: Option<u32>,
+    coop_taskrun: bool,
+    defer_taskrun: bool,
+    single_issuer: bool,
+    submit_policy: SubmitPolicy,
+    wait_strategy: WaitStrategy,
+}
+
+/// Strategy used by [Ring::wait] when there are no completions ready
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Block in the kernel until completions arrive
+    #[default]
+    Block,
+    /// Busy poll the completion queue for the given duration, then block
+    Spin(Duration),
+    /// Block in the kernel until completions arrive or the timeout expires,
+    /// requires `IORING_FEAT_EXT_ARG`, otherwise it blocks indefinitely
+    Timeout(Duration),
+}
+
+/// Policy for submitting queued entries to the kernel, besides when waiting for completions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubmitPolicy {
+    /// Entries are submitted only when waiting, or when the submission queue is full
+    #[default]
+    Wait,
+    /// Entries are submitted as soon as the given number of them are queued
+    Batch(u32),
+    /// Entries are submitted at the end of each executor tick, after polling a batch of tasks
+    Tick,
 }
 
 impl Default for RingOptions {
     fn default() -> Self {
         Self {
             submissions: 2048,
+            completions: None,
             fixed_buffers: 256,
             buffer_groups: 256,
             auto_direct_files: 256,
             manual_direct_files: 16,
+            sqpoll: false,
+            sq_thread_idle: 1000,
+            sq_thread_cpu: None,
+            coop_taskrun: false,
+            defer_taskrun: false,
+            single_issuer: false,
+            submit_policy: SubmitPolicy::Wait,
+            wait_strategy: WaitStrategy::Block,
         }
     }
 }
@@ -77,6 +136,12 @@ impl RingOptions {
         self
     }
 
+    /// Sets the capacity of the completion queue, by default it is twice the submission queue.
+    pub fn completions(mut self, capacity: u32) -> Self {
+        self.completions = Some(capacity);
+        self
+    }
+
     /// Sets the number of available auto direct file descriptors
     /// These are descriptors which are returned by direct operations.
     pub fn auto_direct_files(mut self, capacity: u32) -> Self {
@@ -103,55 +168,268 @@ impl RingOptions {
         self
     }
 
+    /// Enables a kernel thread that polls the submission queue (`IORING_SETUP_SQPOLL`).
+    pub fn sqpoll(mut self, enable: bool) -> Self {
+        self.sqpoll = enable;
+        self
+    }
+
+    /// Sets the milliseconds the submission queue thread can be idle before going to sleep.
+    pub fn sq_thread_idle(mut self, millis: u32) -> Self {
+        self.sq_thread_idle = millis;
+        self
+    }
+
+    /// Pins the submission queue thread to a cpu.
+    pub fn sq_thread_cpu(mut self, cpu: u32) -> Self {
+        self.sq_thread_cpu = Some(cpu);
+        self
+    }
+
+    /// Avoids interrupting the thread when completions are posted (`IORING_SETUP_COOP_TASKRUN`).
+    /// Ignored when sqpoll is enabled.
+    pub fn coop_taskrun(mut self, enable: bool) -> Self {
+        self.coop_taskrun = enable;
+        self
+    }
+
+    /// Defers completion work until the ring is waited on (`IORING_SETUP_DEFER_TASKRUN`).
+    /// Implies single issuer and is ignored when sqpoll is enabled.
+    pub fn defer_taskrun(mut self, enable: bool) -> Self {
+        self.defer_taskrun = enable;
+        self
+    }
+
+    /// Hints that only one thread submits to the ring (`IORING_SETUP_SINGLE_ISSUER`).
+    pub fn single_issuer(mut self, enable: bool) -> Self {
+        self.single_issuer = enable;
+        self
+    }
+
+    /// Sets the policy for submitting queued entries without waiting.
+    pub fn submit_policy(mut self, policy: SubmitPolicy) -> Self {
+        self.submit_policy = policy;
+        self
+    }
+
+    /// Sets the strategy used when waiting for completions.
+    pub fn wait_strategy(mut self, strategy: WaitStrategy) -> Self {
+        self.wait_strategy = strategy;
+        self
+    }
+
+    fn setup(&self) -> RingSetup {
+        if self.sqpoll && (self.coop_taskrun || self.defer_taskrun) {
+            warn!("task run flags are not compatible with sqpoll, ignoring them");
+        }
+
+        RingSetup {
+            sqpoll: self.sqpoll,
+            coop_taskrun: self.coop_taskrun && !self.sqpoll,
+            defer_taskrun: self.defer_taskrun && !self.sqpoll,
+            single_issuer: self.single_issuer || (self.defer_taskrun && !self.sqpoll),
+            ..Default::default()
+        }
+    }
+
+    fn create(&self, setup: &RingSetup) -> Result<IoUring> {
+        let mut builder = IoUring::builder();
+
+        if setup.sqpoll {
+            builder.setup_sqpoll(self.sq_thread_idle);
+            if let Some(cpu) = self.sq_thread_cpu {
+                builder.setup_sqpoll_cpu(cpu);
+            }
+        }
+
+        if let Some(completions) = self.completions {
+            builder.setup_cqsize(completions);
+        }
+
+        if setup.coop_taskrun {
+            builder.setup_coop_taskrun();
+            builder.setup_taskrun_flag();
+        }
+
+        if setup.single_issuer {
+            builder.setup_single_issuer();
+        }
+
+        if setup.defer_taskrun {
+            builder.setup_defer_taskrun();
+        }
+
+        builder.build(self.submissions)
+    }
+
     /// Build the [Ring] instance with the specified options.
+    ///
+    /// # Panics
+    /// If the ring cannot be created, see [RingOptions::try_build].
     pub fn build(self) -> Ring {
+        self.try_build()
+            .unwrap_or_else(|err| panic!("Failed to build ring: {err}"))
+    }
+
+    /// Attempt to build the [Ring] instance with the specified options.
+    /// Returns an error describing the failed step, if the ring cannot be created
+    /// or the requested resources cannot be registered.
+    pub fn try_build(self) -> Result<Ring> {
         if let Err(err) = crate::util::set_limits() {
             warn!(?err, "failed to set max rlimits");
         };
 
-        let ring = IoUring::builder()
-            .build(self.submissions)
-            .expect("Failed to create io_uring");
+        let mut setup = self.setup();
+        let ring = match self.create(&setup) {
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) && setup.has_taskrun_flags() => {
+                warn!(
+                    ?err,
+                    "kernel rejected task run flags, retrying without them"
+                );
+                setup.coop_taskrun = false;
+                setup.defer_taskrun = false;
+                setup.single_issuer = false;
+                self.create(&setup)
+            }
+            res => res,
+        }
+        .map_err(|err| {
+            let hint = match err.raw_os_error() {
+                Some(libc::ENOMEM) => " (RLIMIT_MEMLOCK may be too low)",
+                Some(libc::EPERM) => " (io_uring may be disabled by kernel.io_uring_disabled)",
+                Some(libc::ENOSYS) => " (io_uring is not supported by the kernel)",
+                _ => "",
+            };
+            context(
+                err,
+                format!(
+                    "failed to create io_uring with {} entries{hint}",
+                    self.submissions
+                ),
+            )
+        })?;
+
+        setup.read_params(ring.params());
+        debug!(?setup, "Ring setup");
+
+        let features = Features::probe(&ring);
+        debug!(?features, "Ring features");
+
+        if matches!(self.wait_strategy, WaitStrategy::Timeout(_)) && !setup.ext_arg {
+            warn!("kernel does not support wait timeouts, waiting will block");
+        }
 
         if self.fixed_buffers > 0 {
             ring.submitter()
                 .register_buffers_sparse(self.fixed_buffers)
-                .expect("Failed to register buffers sparse");
+                .map_err(|err| {
+                    let msg = format!("failed to register {} fixed buffers", self.fixed_buffers);
+                    context(err, msg)
+                })?;
         }
 
-        if self.auto_direct_files + self.manual_direct_files > 0 {
+        let direct_files = self.auto_direct_files + self.manual_direct_files;
+        if direct_files > 0 {
             ring.submitter()
-                .register_files_sparse(self.auto_direct_files + self.manual_direct_files)
-                .expect("Failed to register files sparse");
+                .register_files_sparse(direct_files)
+                .map_err(|err| {
+                    let msg = format!("failed to register {direct_files} direct files");
+                    context(err, msg)
+                })?;
         }
 
         if self.auto_direct_files > 0 {
             ring.submitter()
                 .register_file_alloc_range(self.manual_direct_files, self.auto_direct_files)
-                .expect("Failed to register files auto allocation range");
+                .map_err(|err| {
+                    let msg = format!(
+                        "failed to register allocation range for {} auto direct files",
+                        self.auto_direct_files
+                    );
+                    context(err, msg)
+                })?;
 
             if self.manual_direct_files > 0 {
                 ring.submitter()
                     .register_files_update(0, &vec![0; self.manual_direct_files as usize])
-                    .expect("Failed to reserve manual direct files");
+                    .map_err(|err| {
+                        let msg = format!(
+                            "failed to reserve {} manual direct files",
+                            self.manual_direct_files
+                        );
+                        context(err, msg)
+                    })?;
             }
         }
 
-        Ring {
+        Ok(Ring {
             ring,
+            setup,
+            features,
+            policy: self.submit_policy,
+            strategy: self.wait_strategy,
             active: 0,
             detached: 0,
             canceled: 0,
+            backlog: VecDeque::new(),
+            chain: Vec::new(),
+            dropped: 0,
+            counters: Counters::default(),
             completions: CompletionSet::with_capacity(self.submissions as usize),
             direct_files: SlotRegister::new(self.manual_direct_files),
             fixed_buffers: SlotRegister::new(self.fixed_buffers),
             buffer_groups: SlotRegister::new(self.buffer_groups),
-        }
+        })
     }
 }
 
-const SUBMISSION_QUEUE_FULL_ERROR_MESSAGE: &str =
-    "Submission queue is full, consider allocating more submissions";
+/// Returns true if the entry is linked to the next one submitted
+fn is_linked(entry: &Entry) -> bool {
+    // SAFETY: an entry is a `repr(C)` wrapper over `io_uring_sqe`, which starts
+    //         with the opcode and flags bytes
+    let flags = unsafe { *(entry as *const Entry as *const u8).add(1) };
+    flags & (Flags::IO_LINK | Flags::IO_HARDLINK).bits() != 0
+}
+
+/// Number of entries in the chain at the start of the slice
+fn chain_len(entries: &[Entry]) -> usize {
+    entries
+        .iter()
+        .position(|entry| !is_linked(entry))
+        .map_or(entries.len(), |end| end + 1)
+}
+
+fn context(err: Error, msg: String) -> Error {
+    Error::new(err.kind(), format!("{msg}: {err}"))
+}
+
+/// Setup flags and features accepted by the kernel when creating a [Ring]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RingSetup {
+    pub sqpoll: bool,
+    pub coop_taskrun: bool,
+    pub defer_taskrun: bool,
+    pub single_issuer: bool,
+    pub submissions: u32,
+    pub completions: u32,
+    pub nodrop: bool,
+    pub ext_arg: bool,
+    pub fast_poll: bool,
+}
+
+impl RingSetup {
+    fn has_taskrun_flags(&self) -> bool {
+        self.coop_taskrun || self.defer_taskrun || self.single_issuer
+    }
+
+    fn read_params(&mut self, params: &Parameters) {
+        self.submissions = params.sq_entries();
+        self.completions = params.cq_entries();
+        self.nodrop = params.is_feature_nodrop();
+        self.ext_arg = params.is_feature_ext_arg();
+        self.fast_poll = params.is_feature_fast_poll();
+    }
+}
 
 /// Wrapper over an [IoUring] instance.
 ///
@@ -163,9 +441,18 @@ const SUBMISSION_QUEUE_FULL_ERROR_MESSAGE: &str =
 ///  - buffer groups
 pub struct Ring {
     ring: IoUring,
+    setup: RingSetup,
+    features: Features,
+    policy: SubmitPolicy,
+    strategy: WaitStrategy,
     active: u32,
     detached: u32,
     canceled: u32,
+    backlog: VecDeque<Entry>,
+    /// Linked entries waiting for the end of their chain
+    chain: Vec<Entry>,
+    dropped: u32,
+    counters: Counters,
     completions: CompletionSet,
     direct_files: SlotRegister<DirectSlot>,
     fixed_buffers: SlotRegister<BufferSlot>,
@@ -178,17 +465,77 @@ impl Default for Ring {
     }
 }
 
+/// The descriptor of the ring, which becomes readable when there are completions
+/// to handle, so it can be polled by another event loop
+impl AsRawFd for Ring {
+    fn as_raw_fd(&self) -> RawFd {
+        self.ring.as_raw_fd()
+    }
+}
+
 impl Ring {
     pub fn options() -> RingOptions {
         RingOptions::default()
     }
 
+    /// Returns the setup flags and features accepted by the kernel.
+    pub fn setup(&self) -> &RingSetup {
+        &self.setup
+    }
+
+    /// Returns the opcodes supported by the kernel.
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// Returns true if the kernel supports the opcode.
+    pub fn supports(&self, opcode: u8) -> bool {
+        self.features.supports(opcode)
+    }
+
     /// Returns number of active submissions.
     /// A submission is considered active if it has an associated [Waker]
     pub fn active(&self) -> u32 {
         self.active
     }
 
+    /// Returns number of entries waiting for room in the submission queue.
+    pub fn backlogged(&self) -> usize {
+        self.backlog.len()
+    }
+
+    /// Returns the total number of entries submitted.
+    pub fn submitted(&self) -> u64 {
+        self.counters.submitted
+    }
+
+    /// Returns a snapshot of the ring counters, queue depths and resource usage.
+    pub fn metrics(&mut self) -> RingMetrics {
+        let sq_depth = self.ring.submission().len();
+        let (cq_depth, cq_dropped) = {
+            let completion = self.ring.completion();
+            (completion.len(), completion.overflow())
+        };
+
+        RingMetrics {
+            submitted: self.counters.submitted,
+            flushes: self.counters.flushes,
+            completed: self.counters.completed,
+            cancelled: self.counters.cancelled,
+            active: self.active,
+            detached: self.detached,
+            sq_depth,
+            cq_depth,
+            backlogged: self.backlog.len(),
+            sq_overflow: self.counters.sq_overflow,
+            cq_overflow: self.counters.cq_overflow,
+            cq_dropped,
+            fixed_buffers: self.fixed_buffers.usage(),
+            direct_files: self.direct_files.usage(),
+            buffer_groups: self.buffer_groups.usage(),
+        }
+    }
+
     /// Returns true if:
     ///  - all sqes have been completed
     ///  - all cqes have been consumed
@@ -215,10 +562,7 @@ impl Ring {
 
         debug!(key = key.as_u64(), ?entry, "Submission");
 
-        self.ring
-            .submission()
-            .push(&entry.user_data(key.as_u64()))
-            .expect(SUBMISSION_QUEUE_FULL_ERROR_MESSAGE);
+        self.push(entry.user_data(key.as_u64()));
 
         self.active += 1;
 
@@ -235,10 +579,7 @@ impl Ring {
     pub(crate) unsafe fn submit_detached(&mut self, entry: Entry) {
         debug!(?entry, "Detached");
 
-        self.ring
-            .submission()
-            .push(&entry.user_data(IGNORE_KEY))
-            .expect(SUBMISSION_QUEUE_FULL_ERROR_MESSAGE);
+        self.push(entry.user_data(IGNORE_KEY));
 
         self.detached += 1;
     }
@@ -255,17 +596,162 @@ impl Ring {
 
         debug!(key = key.as_u64(), ?entry, "Cancel");
 
+        self.counters.cancelled += 1;
+
         if let Some(entry) = entry {
-            self.ring
-                .submission()
-                .push(&entry.user_data(IGNORE_KEY))
-                .expect(SUBMISSION_QUEUE_FULL_ERROR_MESSAGE);
+            self.push(entry.user_data(IGNORE_KEY));
 
             self.detached += 1;
             self.canceled += 1;
         }
     }
 
+    /// Push an entry to the submission queue. Linked entries are held until the end of their
+    /// chain, which is then pushed as a whole so that a flush never splits it.
+    unsafe fn push(&mut self, entry: Entry) {
+        self.counters.submitted += 1;
+
+        if self.chain.is_empty() && !is_linked(&entry) {
+            self.push_chain(std::slice::from_ref(&entry));
+            return;
+        }
+
+        self.chain.push(entry);
+        if self.chain.last().is_some_and(is_linked) {
+            return;
+        }
+
+        // keep the allocation for the next chain
+        let mut chain = std::mem::take(&mut self.chain);
+        self.push_chain(&chain);
+        chain.clear();
+        self.chain = chain;
+    }
+
+    /// Push a whole chain of entries to the submission queue. If there is no room, the queue is
+    /// first flushed to the kernel and, if there is still no room, the chain is kept in the
+    /// backlog until the next wait.
+    unsafe fn push_chain(&mut self, chain: &[Entry]) {
+        if !self.backlog.is_empty() {
+            self.counters.sq_overflow += 1;
+            self.backlog.extend(chain.iter().cloned());
+            return;
+        }
+
+        if self.ring.submission().push_multiple(chain).is_ok() {
+            if let SubmitPolicy::Batch(size) = self.policy {
+                if self.ring.submission().len() >= size as usize {
+                    self.flush_submissions();
+                }
+            }
+
+            return;
+        }
+
+        // the queue only holds whole chains, so it can be flushed
+        if self.flush_submissions() && self.ring.submission().push_multiple(chain).is_ok() {
+            return;
+        }
+
+        warn!(
+            capacity = self.setup.submissions,
+            "Submission queue overflow"
+        );
+        self.counters.sq_overflow += 1;
+        self.backlog.extend(chain.iter().cloned());
+    }
+
+    /// Move as many backlogged entries as possible to the submission queue, keeping linked
+    /// chains together and flushing it to the kernel whenever it fills up.
+    fn drain_backlog(&mut self) {
+        // nothing else is pushed before submitting, so an unfinished chain cannot wait anymore
+        if !self.chain.is_empty() {
+            debug!(len = self.chain.len(), "Unfinished chain");
+            self.backlog.extend(self.chain.drain(..));
+        }
+
+        if self.backlog.is_empty() {
+            return;
+        }
+
+        let capacity = self.ring.submission().capacity();
+        while !self.backlog.is_empty() {
+            let backlog = self.backlog.make_contiguous();
+
+            let mut len = chain_len(backlog);
+            if len > capacity {
+                warn!(
+                    len,
+                    capacity, "Linked chain does not fit in the submission queue"
+                );
+                len = capacity;
+            }
+
+            if unsafe { self.ring.submission().push_multiple(&backlog[..len]) }.is_ok() {
+                self.backlog.drain(..len);
+                continue;
+            }
+
+            if !self.flush_submissions() || capacity - self.ring.submission().len() < len {
+                break;
+            }
+        }
+
+        debug!(remaining = self.backlog.len(), "Drained backlog");
+    }
+
+    /// Submit queued entries without waiting, returns false if the kernel
+    /// could not accept them because of completion queue overflow.
+    fn flush_submissions(&mut self) -> bool {
+        loop {
+            match self.ring.submit() {
+                Ok(_) => {
+                    self.counters.flushes += 1;
+                    return true;
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) if err.raw_os_error() == Some(libc::EBUSY) => return false,
+                Err(err) => panic!("Failed to submit events: {err:?}"),
+            }
+        }
+    }
+
+    /// Enters the kernel asking for completions without waiting for any,
+    /// so that deferred task work posts the ones that are ready.
+    fn run_task_work(&mut self) {
+        loop {
+            // SAFETY: nothing is submitted and no argument is passed
+            let res = unsafe {
+                self.ring
+                    .submitter()
+                    .enter::<libc::sigset_t>(0, 0, IORING_ENTER_GETEVENTS, None)
+            };
+
+            match res {
+                Ok(_) => return,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) if err.raw_os_error() == Some(libc::EBUSY) => return,
+                Err(err) => panic!("Failed to get events: {err:?}"),
+            }
+        }
+    }
+
+    /// Submit all queued entries to the kernel, without waiting for completions.
+    pub fn flush(&mut self) {
+        self.drain_backlog();
+
+        if !self.ring.submission().is_empty() {
+            self.flush_submissions();
+        }
+    }
+
+    /// Marks the end of an executor tick, flushing queued entries if the [SubmitPolicy] asks for it.
+    pub fn tick(&mut self) {
+        if self.policy == SubmitPolicy::Tick {
+            self.flush();
+        }
+    }
+
     /// Attempt to get the next result of an entry.
     pub(crate) fn check_result(&mut self, key: Key) -> Option<RingResult> {
         self.completions.result(key)
@@ -273,11 +759,76 @@ impl Ring {
 
     /// Blocks until one ore more completions and triggers their associated [Waker]s
     pub fn wait(&mut self) {
-        self.submit_and_wait();
+        self.drain_backlog();
+
+        match self.strategy {
+            WaitStrategy::Block => self.submit_and_wait(None),
+            WaitStrategy::Spin(duration) => {
+                if !self.spin(duration) {
+                    self.submit_and_wait(None);
+                }
+            }
+            WaitStrategy::Timeout(timeout) => self.submit_and_wait(Some(timeout)),
+        }
+
         self.handle_completions();
     }
 
-    fn submit_and_wait(&mut self) {
+    /// Submits queued entries and triggers the [Waker]s of the completions
+    /// that are already available, without blocking
+    pub fn poll(&mut self) {
+        self.flush();
+
+        // NOTE: deferred task work only runs when entering the kernel, and with
+        //       `defer_taskrun` only if it is asked for completions
+        if self.setup.defer_taskrun {
+            self.run_task_work();
+        } else if self.ring.submission().taskrun() {
+            self.flush_submissions();
+        }
+
+        // cancellations might complete here, so the next wait must not expect them
+        self.canceled = 0;
+        self.handle_completions();
+    }
+
+    /// Blocks until one or more completions, or until the timeout expires,
+    /// and triggers their associated [Waker]s
+    pub fn wait_timeout(&mut self, timeout: Duration) {
+        self.drain_backlog();
+
+        self.submit_and_wait(Some(timeout));
+        self.handle_completions();
+    }
+
+    /// Busy polls the completion queue for the given duration,
+    /// returns true if there are completions ready to be handled.
+    fn spin(&mut self, duration: Duration) -> bool {
+        // NOTE: without a syscall, deferred task work never runs and pending cancellations
+        //       expect a blocking wait for their completions
+        if self.setup.defer_taskrun || self.canceled > 0 {
+            return false;
+        }
+
+        self.flush();
+
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            if !self.ring.completion().is_empty() {
+                return true;
+            }
+
+            if self.ring.submission().taskrun() {
+                self.flush_submissions();
+            }
+
+            std::hint::spin_loop();
+        }
+
+        false
+    }
+
+    fn submit_and_wait(&mut self, timeout: Option<Duration>) {
         if self.active + self.detached == 0 {
             return;
         }
@@ -292,6 +843,11 @@ impl Ring {
             want = self.detached;
         }
 
+        // entries left in the backlog are counted, but cannot complete yet
+        if !self.backlog.is_empty() {
+            want = want.min(1);
+        }
+
         debug!(
             active =? self.active,
             detached =? self.detached,
@@ -299,9 +855,27 @@ impl Ring {
             ?want, "Waiting"
         );
 
-        self.ring
-            .submit_and_wait(want as usize)
-            .expect("Failed to wait for events");
+        // NOTE: with sqpoll, the submitter only enters the kernel to wake up the
+        //       polling thread, if it went idle, or to wait for completions
+        let timespec = timeout.map(Timespec::from);
+        loop {
+            let res = match timespec.as_ref() {
+                Some(timespec) if self.setup.ext_arg => {
+                    let args = SubmitArgs::new().timespec(timespec);
+                    self.ring.submitter().submit_with_args(want as usize, &args)
+                }
+                _ => self.ring.submit_and_wait(want as usize),
+            };
+
+            match res {
+                Ok(_) => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) if err.raw_os_error() == Some(libc::ETIME) => break,
+                // completion queue overflowed, reap completions before submitting more
+                Err(err) if err.raw_os_error() == Some(libc::EBUSY) => break,
+                Err(err) => panic!("Failed to wait for events: {err:?}"),
+            }
+        }
 
         debug!("Woke up");
 
@@ -309,9 +883,33 @@ impl Ring {
     }
 
     fn handle_completions(&mut self) {
+        loop {
+            self.reap_completions();
+
+            // NOTE: completions that did not fit in the queue are kept by the kernel (with
+            //       `IORING_FEAT_NODROP`) and flushed back when entering it
+            if !self.ring.submission().cq_overflow() {
+                break;
+            }
+
+            debug!("Completion queue overflow");
+            self.counters.cq_overflow += 1;
+            self.flush_submissions();
+        }
+
+        let dropped = self.ring.completion().overflow();
+        if dropped > self.dropped {
+            error!(count = dropped - self.dropped, "Completions dropped");
+            self.dropped = dropped;
+        }
+    }
+
+    fn reap_completions(&mut self) {
         for entry in self.ring.completion() {
             debug!(key = entry.user_data(), ?entry, "Completion");
 
+            self.counters.completed += 1;
+
             if entry.user_data() == IGNORE_KEY {
                 self.detached -= 1;
                 continue;
@@ -380,4 +978,20 @@ impl Ring {
     pub(crate) fn release_buffer_group(&mut self, slot: BufferGroupId) {
         self.buffer_groups.remove(slot);
     }
+
+    /// Unregister the fixed buffer and direct file tables, so the kernel releases the pages
+    /// and files still held by leaked slots. Fixed operations and direct files fail afterwards,
+    /// so this is only meant for tearing the ring down.
+    pub fn unregister_all(&mut self) -> Result<()> {
+        let submitter = self.ring.submitter();
+        for result in [submitter.unregister_buffers(), submitter.unregister_files()] {
+            match result {
+                // nothing was registered
+                Err(err) if err.raw_os_error() == Some(libc::ENXIO) => {}
+                result => result?,
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -2,6 +2,8 @@ use std::marker::PhantomData;
 
 use io_uring::types::DestinationSlot;
 
+use super::SlotUsage;
+
 pub struct SlotRegister<T> {
     vacant: Vec<u32>,
     len: u32,
@@ -98,6 +100,13 @@ impl<T: WrapSlotKey> SlotRegister<T> {
     pub fn is_full(&self) -> bool {
         self.len as usize == self.vacant.len()
     }
+
+    pub fn usage(&self) -> SlotUsage {
+        SlotUsage {
+            used: self.len - self.vacant.len() as u32,
+            capacity: self.size,
+        }
+    }
 }
 
 impl SlotKey {
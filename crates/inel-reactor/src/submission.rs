@@ -1,11 +1,11 @@
 use std::{
     mem::ManuallyDrop,
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
 };
 
 use futures::{future::FusedFuture, Future, Stream};
-use inel_interface::Reactor;
+use inel_interface::{coop, Reactor};
 use pin_project_lite::pin_project;
 
 use crate::{
@@ -86,6 +86,10 @@ where
     type Output = T::Output;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if matches!(self.state, SubmissionState::Submitted(_)) {
+            ready!(coop::poll_proceed(cx));
+        }
+
         let (res, next_state) = match self.state.take() {
             SubmissionState::Initial => {
                 let entry = self.op.entry();
@@ -105,6 +109,7 @@ where
                         SubmissionState::Completed
                     };
 
+                    coop::consume();
                     (Poll::Ready(op.result(result)), next)
                 }
             },
@@ -137,6 +142,10 @@ where
     type Item = T::Output;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if matches!(self.state, SubmissionState::Submitted(_)) {
+            ready!(coop::poll_proceed(cx));
+        }
+
         let (res, next_state) = match self.state.take() {
             SubmissionState::Initial => {
                 let entry = self.op.entry();
@@ -159,6 +168,7 @@ where
                         SubmissionState::Completed
                     };
 
+                    coop::consume();
                     (Poll::Ready(Some(value)), next)
                 }
             },
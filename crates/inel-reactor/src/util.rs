@@ -100,6 +100,18 @@ pub fn getpeername(sock: RawFd) -> Result<SocketAddr> {
         .map(|_| from_raw_addr(&unsafe { addr.assume_init() }, len))
 }
 
+pub fn setsockopt<T>(sock: RawFd, level: i32, name: i32, value: &T) -> Result<()> {
+    check_ret(unsafe {
+        libc::setsockopt(
+            sock,
+            level,
+            name,
+            value as *const T as *const libc::c_void,
+            mem::size_of::<T>() as libc::socklen_t,
+        )
+    })
+}
+
 pub fn set_limits() -> Result<()> {
     let mut limit = libc::rlimit64 {
         rlim_cur: 0,
@@ -0,0 +1,152 @@
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::helpers::{assert_ready, poll, runtime, ScopedReactor};
+use futures::future::FusedFuture;
+use inel_interface::Reactor;
+use inel_reactor::op::{self, OpExt};
+
+fn supported(reactor: &ScopedReactor) -> bool {
+    reactor.with(|ring| ring.features().futex()).unwrap()
+}
+
+#[test]
+fn wait_wake() {
+    let (reactor, notifier) = runtime();
+    if !supported(&reactor) {
+        return;
+    }
+
+    let word = Arc::new(AtomicU32::new(0));
+
+    let mut wait = pin!(op::FutexWait::new(word.clone(), 0).run_on(reactor.clone()));
+    assert!(poll!(wait, notifier).is_pending());
+    reactor.with(|ring| ring.flush());
+
+    reactor.block_on(async {
+        word.store(1, Ordering::Release);
+        let woken = op::FutexWake::new(word.clone(), 1)
+            .run_on(reactor.clone())
+            .await;
+        assert_eq!(woken.unwrap(), 1);
+    });
+
+    assert_eq!(notifier.try_recv(), Some(()));
+    assert!(assert_ready!(poll!(wait, notifier)).is_ok());
+    assert!(wait.is_terminated());
+
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn wait_mismatch() {
+    let reactor = crate::helpers::reactor();
+    if !supported(&reactor) {
+        return;
+    }
+
+    let word = Arc::new(AtomicU32::new(1));
+
+    let res = reactor.block_on(op::FutexWait::new(word, 0).run_on(reactor.clone()));
+    assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    static WORD: AtomicU32 = AtomicU32::new(0);
+    let woken = reactor.block_on(op::FutexWake::new(&WORD, 8).run_on(reactor.clone()));
+    assert_eq!(woken.unwrap(), 0);
+
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn wake_from_thread() {
+    let reactor = crate::helpers::reactor();
+    if !supported(&reactor) {
+        return;
+    }
+
+    let word = Arc::new(AtomicU32::new(0));
+
+    let waker = thread::spawn({
+        let word = word.clone();
+        move || {
+            thread::sleep(Duration::from_millis(10));
+            word.store(1, Ordering::Release);
+            unsafe {
+                libc::syscall(
+                    libc::SYS_futex,
+                    word.as_ptr(),
+                    libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                    i32::MAX,
+                )
+            }
+        }
+    });
+
+    let res = reactor.block_on(op::FutexWait::new(word.clone(), 0).run_on(reactor.clone()));
+    assert!(res.is_ok());
+    assert_eq!(word.load(Ordering::Acquire), 1);
+
+    assert_eq!(waker.join().unwrap(), 1);
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn wait_vectored() {
+    let reactor = crate::helpers::reactor();
+    if !supported(&reactor) {
+        return;
+    }
+
+    let words = [Arc::new(AtomicU32::new(0)), Arc::new(AtomicU32::new(7))];
+
+    let res = reactor.block_on(
+        op::FutexWaitV::new([(words[0].clone(), 0), (words[1].clone(), 0)]).run_on(reactor.clone()),
+    );
+    assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+
+    let waitv = op::FutexWaitV::new(
+        words
+            .iter()
+            .map(|word| (word.clone(), word.load(Ordering::Acquire))),
+    );
+    let wake = op::FutexWake::new(words[1].clone(), 1);
+
+    let (waited, woken) = reactor.block_on(futures::future::join(
+        waitv.run_on(reactor.clone()),
+        async { wake.run_on(reactor.clone()).await },
+    ));
+
+    assert_eq!(waited.unwrap(), 1);
+    assert_eq!(woken.unwrap(), 1);
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn cancel() {
+    let (reactor, notifier) = runtime();
+    if !supported(&reactor) {
+        return;
+    }
+
+    let word = Arc::new(AtomicU32::new(0));
+
+    {
+        let mut wait = pin!(op::FutexWait::new(word.clone(), 0).run_on(reactor.clone()));
+        assert!(poll!(wait, notifier).is_pending());
+        assert_eq!(reactor.active(), 1);
+    }
+
+    while !reactor.is_done() {
+        reactor.wait();
+    }
+
+    let res = reactor.block_on(op::FutexWake::new(word, 1).run_on(reactor.clone()));
+    assert_eq!(res.unwrap(), 0);
+}
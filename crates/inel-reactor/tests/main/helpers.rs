@@ -20,7 +20,7 @@ use rand::Rng;
 use inel_interface::Reactor;
 use inel_reactor::{
     op::{self, OpExt},
-    ring::Ring,
+    ring::{Ring, RingOptions, RingSetup},
     source::DirectAutoFd,
 };
 
@@ -60,18 +60,25 @@ impl ScopedReactor {
     const RESOURCES: u32 = 64;
 
     fn new() -> Self {
+        Self::with_options(|options| options)
+    }
+
+    fn with_options(f: impl FnOnce(RingOptions) -> RingOptions) -> Self {
+        let options = Ring::options()
+            .submissions(Self::RESOURCES)
+            .fixed_buffers(Self::RESOURCES)
+            .auto_direct_files(Self::RESOURCES)
+            .manual_direct_files(Self::RESOURCES);
+
         Self {
-            inner: Rc::new(RefCell::new(
-                Ring::options()
-                    .submissions(Self::RESOURCES)
-                    .fixed_buffers(Self::RESOURCES)
-                    .auto_direct_files(Self::RESOURCES)
-                    .manual_direct_files(Self::RESOURCES)
-                    .build(),
-            )),
+            inner: Rc::new(RefCell::new(f(options).build())),
         }
     }
 
+    pub fn setup(&self) -> RingSetup {
+        *self.inner.borrow().setup()
+    }
+
     pub fn resources(&self) -> u32 {
         Self::RESOURCES
     }
@@ -80,6 +87,10 @@ impl ScopedReactor {
         self.inner.borrow().active()
     }
 
+    pub fn backlogged(&self) -> usize {
+        self.inner.borrow().backlogged()
+    }
+
     pub fn is_done(&self) -> bool {
         self.inner.borrow().is_done()
     }
@@ -171,6 +182,11 @@ pub fn reactor() -> ScopedReactor {
     ScopedReactor::new()
 }
 
+pub fn reactor_with(f: impl FnOnce(RingOptions) -> RingOptions) -> ScopedReactor {
+    setup_tracing();
+    ScopedReactor::with_options(f)
+}
+
 pub fn notifier() -> WakeNotifier {
     WakeNotifier::new()
 }
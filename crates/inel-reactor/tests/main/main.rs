@@ -3,10 +3,12 @@ mod cancellation;
 mod chain;
 mod dir;
 mod file;
+mod futex;
 mod group;
 pub mod helpers;
 mod net;
 mod read;
+mod ring;
 mod timeout;
 mod write;
 
@@ -10,6 +10,7 @@ use std::{
     pin::pin,
     str::FromStr,
     task::Poll,
+    time::Duration,
 };
 
 use inel_interface::Reactor;
@@ -20,6 +21,15 @@ use inel_reactor::{
     util::{getpeername, getsockname},
 };
 
+fn getsockopt(sock: RawFd, level: i32, name: i32) -> i32 {
+    let mut value: i32 = 0;
+    let mut len = std::mem::size_of::<i32>() as u32;
+    let ret =
+        unsafe { libc::getsockopt(sock, level, name, &mut value as *mut _ as *mut _, &mut len) };
+    assert_eq!(ret, 0);
+    value
+}
+
 fn make_addr(ip: &str, port: u16) -> SocketAddr {
     SocketAddr::new(FromStr::from_str(ip).unwrap(), port)
 }
@@ -304,6 +314,30 @@ fn accept_multi_test(reactor: ScopedReactor, sock: RawFd, count: usize) {
     reactor.wait();
 }
 
+fn accept_multi_backlog_test(reactor: ScopedReactor, sock: RawFd) -> op::AcceptBacklog {
+    let notifier = notifier();
+    let backlog = op::AcceptBacklog::new();
+
+    let mut con = op::AcceptMulti::new(&sock)
+        .backlog(&backlog)
+        .run_on(reactor.clone());
+    let mut stream = pin!(&mut con);
+
+    let mut next = pin!(stream.next());
+    assert!(poll!(next, notifier).is_pending());
+
+    reactor.wait();
+    assert_eq!(notifier.try_recv(), Some(()));
+
+    std::mem::drop(con);
+
+    while reactor.active() > 0 {
+        reactor.wait();
+    }
+
+    backlog
+}
+
 fn accept_multi_direct_test(reactor: ScopedReactor, direct: &impl AsSource, count: usize) {
     let notifier = notifier();
 
@@ -361,6 +395,56 @@ fn shutdown_test(reactor: ScopedReactor, sock: RawFd, how: Shutdown) -> Result<(
     complete_op(reactor, op::Shutdown::new(&sock, how))
 }
 
+fn sockopt_test(reactor: ScopedReactor, sock: RawFd) {
+    let opts = [
+        (
+            op::SockOpt::nodelay(true),
+            libc::IPPROTO_TCP,
+            libc::TCP_NODELAY,
+            1,
+        ),
+        (
+            op::SockOpt::keepalive(true),
+            libc::SOL_SOCKET,
+            libc::SO_KEEPALIVE,
+            1,
+        ),
+        (
+            op::SockOpt::keepalive_idle(Duration::from_secs(30)),
+            libc::IPPROTO_TCP,
+            libc::TCP_KEEPIDLE,
+            30,
+        ),
+        (
+            op::SockOpt::keepalive_count(4),
+            libc::IPPROTO_TCP,
+            libc::TCP_KEEPCNT,
+            4,
+        ),
+        (
+            op::SockOpt::reuseport(true),
+            libc::SOL_SOCKET,
+            libc::SO_REUSEPORT,
+            1,
+        ),
+        (op::SockOpt::ttl(42), libc::IPPROTO_IP, libc::IP_TTL, 42),
+    ];
+
+    for (opt, level, name, expected) in opts {
+        assert!(complete_op(reactor.clone(), op::SetSockOpt::new(&sock, opt)).is_ok());
+        assert_eq!(getsockopt(sock, level, name), expected);
+    }
+
+    assert!(op::SockOpt::nodelay(false).apply(sock).is_ok());
+    assert_eq!(getsockopt(sock, libc::IPPROTO_TCP, libc::TCP_NODELAY), 0);
+
+    assert!(complete_op(
+        reactor.clone(),
+        op::SetSockOpt::new(&sock, op::SockOpt::linger(Some(Duration::from_secs(5))))
+    )
+    .is_ok());
+}
+
 fn create_listener(reactor: ScopedReactor, addr: &str) -> (RawFd, u16) {
     let addr = make_addr(addr, 0);
     let sock = create_socket_test(
@@ -413,6 +497,25 @@ fn socket() {
     assert!(reactor.is_done());
 }
 
+#[test]
+fn sockopt() {
+    let (reactor, _) = runtime();
+
+    let sock = create_socket_test(reactor.clone(), libc::AF_INET, libc::SOCK_STREAM);
+    sockopt_test(reactor.clone(), sock);
+
+    let udp = create_socket_test(reactor.clone(), libc::AF_INET, libc::SOCK_DGRAM);
+    let res = complete_op(
+        reactor.clone(),
+        op::SetSockOpt::new(&udp, op::SockOpt::nodelay(true)),
+    );
+    assert!(res.is_err());
+
+    assert!(op::SockOpt::nodelay(true).apply(i32::MAX).is_err());
+
+    assert!(reactor.is_done());
+}
+
 #[test]
 fn connect() {
     let (reactor, _) = runtime();
@@ -478,6 +581,25 @@ fn accept_multi() {
     assert!(reactor.is_done());
 }
 
+#[test]
+fn accept_multi_backlog() {
+    let (reactor, _) = runtime();
+
+    let (sock, port) = create_listener_ipv4(reactor.clone());
+    for _ in 0..4 {
+        connect_test_ipv4(reactor.clone(), port);
+    }
+
+    let backlog = accept_multi_backlog_test(reactor.clone(), sock);
+    assert!(!backlog.is_empty());
+
+    while let Some(fd) = backlog.pop_fd() {
+        assert!(getpeername(fd).is_ok_and(|addr| addr.ip().is_loopback()));
+    }
+
+    assert!(reactor.is_done());
+}
+
 #[test]
 fn shutdown() {
     let (reactor, _) = runtime();
@@ -609,6 +731,25 @@ mod direct {
         assert!(reactor.is_done());
     }
 
+    #[test]
+    fn sockopt() {
+        let (mut reactor, _) = runtime();
+
+        let direct = create_fixed_socket_test(reactor.clone(), libc::AF_INET, libc::SOCK_STREAM);
+        let res = complete_op(
+            reactor.clone(),
+            op::SetSockOpt::new(&direct, op::SockOpt::nodelay(true)),
+        );
+        assert!(res.is_ok());
+
+        let fd = complete_op(reactor.clone(), op::InstallSlot::new(&direct)).unwrap();
+        assert_eq!(getsockopt(fd, libc::IPPROTO_TCP, libc::TCP_NODELAY), 1);
+
+        direct.release(&mut reactor);
+
+        assert!(reactor.is_done());
+    }
+
     #[test]
     fn connect() {
         let (mut reactor, _) = runtime();
@@ -695,6 +836,47 @@ mod direct {
         assert!(reactor.is_done());
     }
 
+    #[test]
+    fn accept_multi_backlog() {
+        let (mut reactor, _) = runtime();
+
+        let (direct, port) = create_fixed_listener_ipv4(reactor.clone());
+        let mut directs = vec![];
+        for _ in 0..4 {
+            directs.push(connect_fixed_test_ipv4(reactor.clone(), port));
+        }
+
+        let notifier = notifier();
+        let backlog = op::AcceptBacklog::new();
+
+        let mut con = op::AcceptMulti::new(&direct)
+            .backlog(&backlog)
+            .direct()
+            .run_on(reactor.clone());
+        let mut stream = pin!(&mut con);
+
+        let mut next = pin!(stream.next());
+        assert!(poll!(next, notifier).is_pending());
+
+        reactor.wait();
+        std::mem::drop(con);
+
+        while reactor.active() > 0 {
+            reactor.wait();
+        }
+
+        assert!(!backlog.is_empty());
+        while let Some(slot) = backlog.pop_direct() {
+            assert!(complete_op(reactor.clone(), op::Close::new(&slot)).is_ok());
+        }
+
+        for direct in directs {
+            direct.release(&mut reactor);
+        }
+
+        assert!(reactor.is_done());
+    }
+
     #[test]
     fn errors() {
         let (reactor, _) = runtime();
@@ -0,0 +1,356 @@
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use inel_interface::Reactor;
+use inel_reactor::{
+    op::{self, OpExt},
+    ring::{Ring, SubmitPolicy, WaitStrategy},
+};
+
+use crate::helpers::{notifier, poll, reactor_with, ScopedReactor, TempFile, MESSAGE};
+
+fn run_nops(reactor: ScopedReactor) {
+    let results = reactor.block_on(join_all((0..16).map(|_| op::Nop.run_on(reactor.clone()))));
+
+    assert_eq!(results.len(), 16);
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn default() {
+    let reactor = reactor_with(|options| options);
+
+    let setup = reactor.setup();
+    assert!(!setup.sqpoll);
+    assert!(!setup.coop_taskrun);
+    assert!(!setup.defer_taskrun);
+    assert!(!setup.single_issuer);
+    assert_eq!(setup.submissions, reactor.resources());
+    assert_eq!(setup.completions, reactor.resources() * 2);
+
+    run_nops(reactor);
+}
+
+#[test]
+fn completions() {
+    let reactor = reactor_with(|options| options.completions(512));
+
+    assert_eq!(reactor.setup().completions, 512);
+
+    run_nops(reactor);
+}
+
+#[test]
+fn sqpoll() {
+    let reactor = reactor_with(|options| options.sqpoll(true).sq_thread_idle(10));
+
+    assert!(reactor.setup().sqpoll);
+
+    run_nops(reactor.clone());
+
+    // let the polling thread go idle, it must be woken up on the next submission
+    std::thread::sleep(Duration::from_millis(50));
+
+    run_nops(reactor);
+}
+
+#[test]
+fn sqpoll_ignores_taskrun() {
+    let reactor =
+        reactor_with(|options| options.sqpoll(true).coop_taskrun(true).defer_taskrun(true));
+
+    let setup = reactor.setup();
+    assert!(setup.sqpoll);
+    assert!(!setup.coop_taskrun);
+    assert!(!setup.defer_taskrun);
+
+    run_nops(reactor);
+}
+
+#[test]
+fn coop_taskrun() {
+    let reactor = reactor_with(|options| options.coop_taskrun(true));
+
+    run_nops(reactor);
+}
+
+#[test]
+fn defer_taskrun() {
+    let reactor = reactor_with(|options| options.defer_taskrun(true));
+
+    let setup = reactor.setup();
+    if setup.defer_taskrun {
+        assert!(setup.single_issuer);
+    }
+
+    run_nops(reactor);
+}
+
+#[test]
+fn submission_overflow() {
+    let reactor = reactor_with(|options| options.submissions(4));
+
+    let notifier = notifier();
+    let mut nops = (0..64)
+        .map(|_| Box::pin(op::Nop.run_on(reactor.clone())))
+        .collect::<Vec<_>>();
+
+    for nop in nops.iter_mut() {
+        assert!(poll!(nop, notifier).is_pending());
+    }
+    assert_eq!(reactor.active(), 64);
+
+    while !nops.is_empty() {
+        reactor.wait();
+        nops.retain_mut(|nop| poll!(nop, notifier).is_pending());
+    }
+
+    assert_eq!(reactor.backlogged(), 0);
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn submission_overflow_chain() {
+    let reactor = reactor_with(|options| options.submissions(4));
+    let notifier = notifier();
+
+    // leave room in the submission queue for the first entry of the chain only
+    let mut nops = (0..3)
+        .map(|_| Box::pin(op::Nop.run_on(reactor.clone())))
+        .collect::<Vec<_>>();
+
+    for nop in nops.iter_mut() {
+        assert!(poll!(nop, notifier).is_pending());
+    }
+
+    let start = Instant::now();
+    let mut timeout = Box::pin(
+        op::Timeout::new(Duration::from_millis(20))
+            .chain()
+            .run_on(reactor.clone()),
+    );
+    let mut linked = Box::pin(op::Nop.run_on(reactor.clone()));
+
+    assert!(poll!(timeout, notifier).is_pending());
+    assert!(poll!(linked, notifier).is_pending());
+
+    // the chain is not split, so the nop still waits for the timeout
+    reactor.block_on(linked);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    reactor.block_on(join_all(nops));
+    reactor.block_on(timeout);
+
+    assert_eq!(reactor.backlogged(), 0);
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn submission_overflow_sqpoll() {
+    let reactor = reactor_with(|options| options.submissions(4).sqpoll(true));
+
+    run_nops(reactor.clone());
+    run_nops(reactor.clone());
+
+    assert_eq!(reactor.backlogged(), 0);
+}
+
+#[test]
+fn completion_overflow() {
+    let reactor = reactor_with(|options| options.submissions(4).completions(8));
+
+    let results = reactor.block_on(join_all((0..128).map(|_| op::Nop.run_on(reactor.clone()))));
+
+    assert_eq!(results.len(), 128);
+    assert!(reactor.is_done());
+}
+
+fn write_submitted(policy: SubmitPolicy, writes: usize, tick: bool) -> bool {
+    let reactor = reactor_with(|options| options.submit_policy(policy));
+    let notifier = notifier();
+    let mut file = TempFile::empty();
+
+    let mut ops = (0..writes)
+        .map(|_| Box::pin(op::Write::new(&file.fd(), vec![b'a'; 8]).run_on(reactor.clone())))
+        .collect::<Vec<_>>();
+
+    for write in ops.iter_mut() {
+        assert!(poll!(write, notifier).is_pending());
+    }
+
+    if tick {
+        reactor.with(|ring| ring.tick());
+    }
+
+    std::thread::sleep(Duration::from_millis(20));
+    let submitted = !file.read().is_empty();
+
+    for write in ops {
+        assert!(reactor.block_on(write).1.is_ok());
+    }
+    assert!(reactor.is_done());
+
+    submitted
+}
+
+#[test]
+fn flush() {
+    let reactor = reactor_with(|options| options);
+    let notifier = notifier();
+    let mut file = TempFile::empty();
+
+    let mut write =
+        Box::pin(op::Write::new(&file.fd(), MESSAGE.as_bytes().to_vec()).run_on(reactor.clone()));
+    assert!(poll!(write, notifier).is_pending());
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(file.read().is_empty());
+
+    reactor.with(|ring| ring.flush());
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(file.read(), MESSAGE);
+
+    let (buf, res) = reactor.block_on(write);
+    assert_eq!(res.unwrap(), buf.len());
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn submit_policy() {
+    assert!(!write_submitted(SubmitPolicy::Wait, 4, true));
+
+    assert!(!write_submitted(SubmitPolicy::Batch(4), 3, false));
+    assert!(write_submitted(SubmitPolicy::Batch(4), 4, false));
+
+    assert!(!write_submitted(SubmitPolicy::Tick, 4, false));
+    assert!(write_submitted(SubmitPolicy::Tick, 4, true));
+}
+
+#[test]
+fn wait_timeout() {
+    let reactor = reactor_with(|options| options);
+    let notifier = notifier();
+
+    let mut timeout = Box::pin(op::Timeout::new(Duration::from_secs(10)).run_on(reactor.clone()));
+    assert!(poll!(timeout, notifier).is_pending());
+
+    let start = Instant::now();
+    reactor.with(|ring| ring.wait_timeout(Duration::from_millis(10)));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    assert!(notifier.try_recv().is_none());
+    assert!(poll!(timeout, notifier).is_pending());
+
+    drop(timeout);
+    while !reactor.is_done() {
+        reactor.wait();
+    }
+}
+
+#[test]
+fn wait_strategy_timeout() {
+    let reactor = reactor_with(|options| {
+        options.wait_strategy(WaitStrategy::Timeout(Duration::from_millis(10)))
+    });
+    let notifier = notifier();
+
+    let mut timeout = Box::pin(op::Timeout::new(Duration::from_secs(10)).run_on(reactor.clone()));
+    assert!(poll!(timeout, notifier).is_pending());
+
+    let start = Instant::now();
+    reactor.wait();
+    reactor.wait();
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    assert!(poll!(timeout, notifier).is_pending());
+
+    drop(timeout);
+    while !reactor.is_done() {
+        reactor.wait();
+    }
+}
+
+#[test]
+fn wait_strategy_spin() {
+    let reactor = reactor_with(|options| {
+        options.wait_strategy(WaitStrategy::Spin(Duration::from_millis(50)))
+    });
+
+    run_nops(reactor.clone());
+
+    let short = op::Timeout::new(Duration::from_millis(5)).run_on(reactor.clone());
+    reactor.block_on(short);
+
+    let long = op::Timeout::new(Duration::from_millis(100)).run_on(reactor.clone());
+    reactor.block_on(long);
+
+    assert!(reactor.is_done());
+}
+
+#[test]
+fn metrics() {
+    let reactor = reactor_with(|options| options);
+    let notifier = notifier();
+
+    let metrics = reactor.with(|ring| ring.metrics()).unwrap();
+    assert_eq!(metrics.submitted, 0);
+    assert_eq!(metrics.fixed_buffers.used, 0);
+    assert_eq!(metrics.fixed_buffers.capacity, reactor.resources());
+    assert_eq!(metrics.direct_files.capacity, reactor.resources());
+
+    run_nops(reactor.clone());
+
+    let mut timeout = Box::pin(op::Timeout::new(Duration::from_secs(10)).run_on(reactor.clone()));
+    assert!(poll!(timeout, notifier).is_pending());
+
+    let metrics = reactor.with(|ring| ring.metrics()).unwrap();
+    assert_eq!(metrics.submitted, 17);
+    assert_eq!(metrics.completed, 16);
+    assert_eq!(metrics.active, 1);
+    assert_eq!(metrics.sq_depth, 1);
+
+    drop(timeout);
+    while !reactor.is_done() {
+        reactor.wait();
+    }
+
+    let metrics = reactor.with(|ring| ring.metrics()).unwrap();
+    assert_eq!(metrics.submitted, 18);
+    assert_eq!(metrics.completed, 18);
+    assert_eq!(metrics.cancelled, 1);
+    assert_eq!(metrics.active, 0);
+    assert_eq!(metrics.sq_depth, 0);
+    assert_eq!(metrics.cq_depth, 0);
+}
+
+#[test]
+fn features() {
+    let reactor = reactor_with(|options| options);
+
+    let features = reactor.with(|ring| *ring.features()).unwrap();
+    assert!(features.supports(io_uring::opcode::Nop::CODE));
+    assert!(features.supports(io_uring::opcode::Accept::CODE));
+    assert!(!features.supports(u8::MAX));
+
+    assert!(reactor
+        .with(|ring| ring.supports(io_uring::opcode::Timeout::CODE))
+        .unwrap());
+}
+
+#[test]
+fn try_build() {
+    let err = Ring::options().submissions(0).try_build().err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("failed to create io_uring"));
+
+    let err = Ring::options()
+        .fixed_buffers(1 << 24)
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("fixed buffers"));
+
+    let ring = Ring::options().submissions(8).try_build().unwrap();
+    assert!(ring.is_done());
+}
@@ -1,14 +1,19 @@
-use std::{io::Result, net::ToSocketAddrs};
+use std::{future::Future, io::Result, net::ToSocketAddrs, pin::pin, time::Duration};
 
 use axum::Router;
-use futures::{AsyncRead, AsyncWrite, Stream, StreamExt};
+use futures::{
+    future::{self, LocalBoxFuture, Shared},
+    AsyncRead, AsyncWrite, FutureExt, Stream, StreamExt,
+};
+use inel_executor::JoinHandle;
 use tower::Service;
 
 use crate::{
     compat,
     group::BufferShareGroup,
     io::{ReadSource, WriteSource},
-    net::TcpListener,
+    net::{Incoming, TcpListener},
+    task::JoinSet,
 };
 
 pub async fn serve<A>(addr: A, app: Router) -> Result<()>
@@ -52,11 +57,15 @@ enum HttpProto {
     Http2,
 }
 
+type Signal = Shared<LocalBoxFuture<'static, ()>>;
+
 pub struct Serve {
     descriptors: Descriptors,
     buffering: Buffering,
     security: Tls,
     http: HttpProto,
+    shutdown: Option<Signal>,
+    shutdown_timeout: Option<Duration>,
 }
 
 impl Default for Serve {
@@ -66,6 +75,8 @@ impl Default for Serve {
             buffering: Buffering::Simple,
             security: Tls::None,
             http: HttpProto::Http1,
+            shutdown: None,
+            shutdown_timeout: None,
         }
     }
 }
@@ -101,6 +112,23 @@ impl Serve {
         self
     }
 
+    /// Stops accepting connections when `signal` completes, then gracefully shuts down
+    /// every open connection and waits for them to finish before returning
+    pub fn with_graceful_shutdown<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        self.shutdown = Some(signal.boxed_local().shared());
+        self
+    }
+
+    /// Sets the maximum time to wait for open connections after a graceful shutdown,
+    /// the ones still open afterwards are aborted
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(timeout);
+        self
+    }
+
     pub async fn serve<A>(self, addr: A, app: Router) -> Result<()>
     where
         A: ToSocketAddrs,
@@ -119,79 +147,130 @@ impl Serve {
         Ok(())
     }
 
+    /// Serves the connections of a listener shard, see [TcpListener::bind_sharded].
+    /// The shard is already bound, so direct descriptors are not used.
+    pub async fn serve_incoming(self, incoming: Incoming, app: Router) {
+        self.with_incoming(incoming, app).await
+    }
+
     async fn with_incoming<S, I>(self, incoming: I, app: Router)
     where
         I: Stream<Item = Result<S>>,
         S: ReadSource + WriteSource + Unpin + 'static,
     {
-        incoming
-            .filter_map(async |stream| stream.ok())
-            .for_each(async |stream| {
+        let signal = self
+            .shutdown
+            .clone()
+            .unwrap_or_else(|| future::pending().boxed_local().shared());
+
+        let mut connections = Connections::default();
+
+        // the listener is dropped with the stream, so no connections are accepted while draining
+        {
+            let mut incoming = pin!(incoming.take_until(signal.clone()));
+
+            while let Some(stream) = incoming.next().await {
+                // forget the connections that are already closed
+                while connections.0.try_join_next().is_some() {}
+
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
                 let app = app.clone();
+                let signal = signal.clone();
 
-                match &self.buffering {
+                let handle = match &self.buffering {
                     Buffering::Simple => {
                         let stream = compat::stream::BufStream::new(stream);
-                        self.with_raw_stream(stream, app).await;
+                        self.spawn_connection(stream, app, signal)
                     }
 
-                    Buffering::Fixed => {
-                        if let Ok(stream) = compat::stream::FixedBufStream::new(stream) {
-                            self.with_raw_stream(stream, app).await;
-                        }
-                    }
+                    Buffering::Fixed => match compat::stream::FixedBufStream::new(stream) {
+                        Ok(stream) => self.spawn_connection(stream, app, signal),
+                        Err(_) => continue,
+                    },
 
                     Buffering::Group(share) => {
                         let stream = compat::stream::ShareBufStream::new(stream, share);
-                        self.with_raw_stream(stream, app).await;
+                        self.spawn_connection(stream, app, signal)
                     }
+                };
+
+                connections.0.insert(handle);
+            }
+        }
+
+        let drained = async { while connections.0.join_next().await.is_some() {} };
+
+        match self.shutdown_timeout {
+            Some(timeout) => {
+                if crate::time::timeout(timeout, drained).await.is_err() {
+                    connections.0.abort_all();
+                    while connections.0.join_next().await.is_some() {}
                 }
-            })
-            .await;
+            }
+            None => drained.await,
+        }
     }
 
-    async fn with_raw_stream<S>(&self, stream: S, app: Router)
+    fn spawn_connection<S>(&self, stream: S, app: Router, signal: Signal) -> JoinHandle<()>
     where
         S: AsyncRead + AsyncWrite + Unpin + 'static,
     {
-        match &self.security {
-            Tls::None => self.with_stream(stream, app).await,
-            #[cfg(feature = "rustls")]
-            Tls::Rustls(acceptor) => {
-                if let Ok(tls_stream) = acceptor.accept(stream).await {
-                    self.with_stream(tls_stream, app).await;
+        let security = self.security.clone();
+        let http = self.http.clone();
+
+        crate::spawn(async move {
+            match security {
+                Tls::None => handle_http(stream, http, app, signal).await,
+                #[cfg(feature = "rustls")]
+                Tls::Rustls(acceptor) => {
+                    if let Ok(tls_stream) = acceptor.accept(stream).await {
+                        handle_http(tls_stream, http, app, signal).await;
+                    }
                 }
             }
-        }
+        })
     }
+}
 
-    async fn with_stream<S>(&self, stream: S, app: Router)
-    where
-        S: AsyncRead + AsyncWrite + Unpin + 'static,
-    {
-        match self.http {
-            HttpProto::Http1 => crate::spawn(handle_http1(stream, app)).detach(),
-            HttpProto::Http2 => crate::spawn(handle_http2(stream, app)).detach(),
-        }
+/// Open connections, which are detached if serving stops before the shutdown
+#[derive(Default)]
+struct Connections(JoinSet<()>);
+
+impl Drop for Connections {
+    fn drop(&mut self) {
+        self.0.detach_all();
+    }
+}
+
+async fn handle_http<S>(stream: S, http: HttpProto, app: Router, signal: Signal)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match http {
+        HttpProto::Http1 => handle_http1(stream, app, signal).await,
+        HttpProto::Http2 => handle_http2(stream, app, signal).await,
     }
 }
 
-async fn handle_http1<S>(stream: S, app: Router)
+async fn handle_http1<S>(stream: S, app: Router, signal: Signal)
 where
     S: AsyncRead + AsyncWrite + Unpin,
 {
     let service = hyper::service::service_fn(|request| app.clone().call(request));
-    if let Err(err) = compat::hyper::serve_http1(stream, service).await {
+    if let Err(err) = compat::hyper::serve_http1_graceful(stream, service, signal).await {
         tracing::error!(?err);
     }
 }
 
-async fn handle_http2<S>(stream: S, app: Router)
+async fn handle_http2<S>(stream: S, app: Router, signal: Signal)
 where
     S: AsyncRead + AsyncWrite + Unpin,
 {
     let service = hyper::service::service_fn(|request| app.clone().call(request));
-    if let Err(err) = compat::hyper::serve_http2(stream, service).await {
+    if let Err(err) = compat::hyper::serve_http2_graceful(stream, service, signal).await {
         tracing::error!(?err);
     }
 }
@@ -1,11 +1,14 @@
 use std::{
     future::Future,
     io::Result,
-    pin::Pin,
+    pin::{pin, Pin},
     task::{ready, Context, Poll},
 };
 
-use futures::{AsyncRead, AsyncWrite};
+use futures::{
+    future::{self, Either},
+    AsyncRead, AsyncWrite,
+};
 use hyper::{
     body::{Body, Incoming},
     service::HttpService,
@@ -139,13 +142,53 @@ where
     B: Body + 'static,
     <B as Body>::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-    let hyper = HyperStream::new(stream);
-    hyper::server::conn::http1::Builder::new()
-        .serve_connection(hyper, app)
-        .await
+    serve_http1_graceful(stream, app, future::pending()).await
 }
 
 pub async fn serve_http2<S, A, B, E, F>(stream: S, app: A) -> hyper::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    A: HttpService<Incoming, ResBody = B, Error = E, Future = F>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    F: Future<Output = std::result::Result<Response<B>, E>> + Send + 'static,
+    B: Body + Send + 'static,
+    <B as Body>::Data: Send,
+    <B as Body>::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    serve_http2_graceful(stream, app, future::pending()).await
+}
+
+/// Like [serve_http1], but starts a graceful shutdown of the connection when `signal` completes
+pub async fn serve_http1_graceful<S, A, B, E>(
+    stream: S,
+    app: A,
+    signal: impl Future<Output = ()>,
+) -> hyper::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    A: HttpService<Incoming, ResBody = B, Error = E>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    B: Body + 'static,
+    <B as Body>::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let hyper = HyperStream::new(stream);
+    let mut conn = pin!(hyper::server::conn::http1::Builder::new().serve_connection(hyper, app));
+
+    match future::select(conn.as_mut(), pin!(signal)).await {
+        Either::Left((res, _)) => res,
+        Either::Right(((), _)) => {
+            conn.as_mut().graceful_shutdown();
+            conn.await
+        }
+    }
+}
+
+/// Like [serve_http2], but starts a graceful shutdown of the connection when `signal` completes
+pub async fn serve_http2_graceful<S, A, B, E, F>(
+    stream: S,
+    app: A,
+    signal: impl Future<Output = ()>,
+) -> hyper::Result<()>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     A: HttpService<Incoming, ResBody = B, Error = E, Future = F>,
@@ -156,7 +199,14 @@ where
     <B as Body>::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     let hyper = HyperStream::new(stream);
-    hyper::server::conn::http2::Builder::new(Executor)
-        .serve_connection(hyper, app)
-        .await
+    let mut conn =
+        pin!(hyper::server::conn::http2::Builder::new(Executor).serve_connection(hyper, app));
+
+    match future::select(conn.as_mut(), pin!(signal)).await {
+        Either::Left((res, _)) => res,
+        Either::Right(((), _)) => {
+            conn.as_mut().graceful_shutdown();
+            conn.await
+        }
+    }
 }
@@ -7,6 +7,7 @@ use std::{
 };
 
 use futures::{AsyncBufRead, AsyncRead, FutureExt};
+use inel_interface::coop;
 
 use crate::{
     buffer::{StableBuffer, StableBufferExt, StableBufferMut, View},
@@ -92,6 +93,8 @@ where
     Adapter: BufReaderAdapter<S, B, F>,
 {
     fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        ready!(coop::poll_proceed(cx));
+
         let this = Pin::into_inner(self);
 
         match &mut this.state {
@@ -125,6 +128,8 @@ where
 
                     return Pin::new(this).poll_fill_buf(cx);
                 }
+
+                coop::consume();
             }
         };
 
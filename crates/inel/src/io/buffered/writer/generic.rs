@@ -7,6 +7,7 @@ use std::{
 };
 
 use futures::{AsyncWrite, FutureExt};
+use inel_interface::coop;
 
 use crate::{
     buffer::{StableBufferExt, StableBufferMut, View},
@@ -86,6 +87,8 @@ where
     Adapter: BufWriterAdapter<S, B, F>,
 {
     fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        ready!(coop::poll_proceed(cx));
+
         let this = self.get_mut();
 
         match &mut this.state {
@@ -101,6 +104,7 @@ where
                 } else {
                     let len = spare_capacity.min(buf.len());
                     let res = internal.fill(&buf[..len]);
+                    coop::consume();
                     Poll::Ready(Ok(res))
                 }
             }
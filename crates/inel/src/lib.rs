@@ -1,13 +1,29 @@
 use core::{cell::RefCell, future::Future};
+use std::{
+    io::{Error, Result},
+    os::fd::{AsRawFd, RawFd},
+    time::{Duration, Instant},
+};
 
 use inel_executor::{Executor, JoinHandle};
 use inel_reactor::ring::Ring;
 
-pub use inel_reactor::ring::RingOptions;
+pub use inel_executor::ExecutorMetrics;
+pub use inel_reactor::ring::{
+    Features, RingMetrics, RingOptions, SlotUsage, SubmitPolicy, WaitStrategy,
+};
 
 thread_local! {
     static EXECUTOR: RefCell<Executor> = RefCell::new(Executor::new());
-    static REACTOR: RefCell<Ring> = RefCell::new(Ring::default());
+    static REACTOR: RefCell<Option<Ring>> = const { RefCell::new(None) };
+}
+
+/// Runs the closure on the reactor of the current thread, building a default one if needed
+fn with_reactor<F, T>(f: F) -> T
+where
+    F: FnOnce(&mut Ring) -> T,
+{
+    REACTOR.with_borrow_mut(|react| f(react.get_or_insert_with(Ring::default)))
 }
 
 struct GlobalReactor;
@@ -15,38 +31,79 @@ impl inel_interface::Reactor for GlobalReactor {
     type Handle = Ring;
 
     fn wait(&self) {
-        REACTOR.with_borrow_mut(|react| react.wait());
+        with_reactor(|react| react.wait());
+    }
+
+    fn wait_timeout(&self, timeout: Duration) {
+        with_reactor(|react| react.wait_timeout(timeout));
     }
 
     fn with<F, T>(&self, f: F) -> Option<T>
     where
         F: FnOnce(&mut Self::Handle) -> T,
     {
-        REACTOR.try_with(|react| f(&mut react.borrow_mut())).ok()
+        REACTOR
+            .try_with(|react| f(react.borrow_mut().get_or_insert_with(Ring::default)))
+            .ok()
+    }
+
+    fn tick(&self) {
+        with_reactor(|react| react.tick());
+    }
+
+    fn poll(&self) {
+        with_reactor(|react| react.poll());
     }
 }
 
-pub use inel_macro::main;
+pub use inel_executor::task_local;
+pub use inel_macro::{join, main, select, test};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use core::{future::poll_fn, pin::pin, task::Poll};
+    pub use futures::future::maybe_done;
+}
 
 pub mod buffer;
 pub mod fs;
 pub mod group;
 pub mod io;
 pub mod net;
+pub mod sync;
+pub mod task;
 pub mod time;
 mod util;
 
 #[cfg(feature = "compat")]
 pub mod compat;
 
+mod runtime;
 mod source;
 
+pub use runtime::{EnterGuard, Runtime, SHUTDOWN_TIMEOUT};
+
+/// Initializes the reactor of the current thread with the given options.
+///
+/// # Panics
+/// If the reactor is already running or cannot be built, see [try_init].
 pub fn init(options: RingOptions) {
-    if REACTOR.with_borrow(|react| !react.is_done()) {
-        panic!("Tried to init already running reactor");
+    if let Err(err) = try_init(options) {
+        panic!("Tried to init reactor: {err}");
+    }
+}
+
+/// Attempt to initialize the reactor of the current thread with the given options.
+/// Fails if the reactor is already running or if the ring cannot be built.
+pub fn try_init(options: RingOptions) -> Result<()> {
+    if !is_done() {
+        return Err(Error::other("reactor is already running"));
     }
 
-    REACTOR.set(options.build());
+    let ring = options.try_build()?;
+    REACTOR.set(Some(ring));
+
+    Ok(())
 }
 
 #[inline]
@@ -57,6 +114,40 @@ where
     EXECUTOR.with_borrow(|exe| exe.spawn(future))
 }
 
+/// Spawns a task scheduled according to its priority, see [task::Priority]
+#[inline]
+pub fn spawn_with_priority<F>(priority: task::Priority, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+{
+    EXECUTOR.with_borrow(|exe| exe.spawn_with_priority(priority, future))
+}
+
+/// Spawns a task inside a new span with the given name, see [spawn_instrumented]
+pub fn spawn_named<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+{
+    let span = tracing::info_span!(
+        "task",
+        name,
+        polls = tracing::field::Empty,
+        sqes = tracing::field::Empty,
+        busy_us = tracing::field::Empty,
+    );
+
+    spawn_instrumented(future, span)
+}
+
+/// Spawns a task that is polled inside the given span, so that its submissions are
+/// attributed to it. The span records `polls`, `sqes` and `busy_us` fields, if declared.
+pub fn spawn_instrumented<F>(future: F, span: tracing::Span) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+{
+    spawn(task::Traced::new(future, span))
+}
+
 #[inline]
 pub fn block_on<F>(future: F) -> F::Output
 where
@@ -70,9 +161,162 @@ pub fn run() {
     EXECUTOR.with_borrow(|exe| exe.run(GlobalReactor));
 }
 
+/// Polls the ready tasks and handles the completions that are already available, until no
+/// task can make progress without blocking. Returns true if there are tasks left.
+///
+/// Together with [ring_fd] and [turn], this allows driving the runtime from another event loop.
+#[inline]
+pub fn run_until_stalled() -> bool {
+    EXECUTOR.with_borrow(|exe| exe.run_until_stalled(GlobalReactor))
+}
+
+/// Handles completions, waiting up to `timeout` for them if no task is ready, and then
+/// polls a single batch of ready tasks. Returns true if there are tasks left.
+#[inline]
+pub fn turn(timeout: Duration) -> bool {
+    EXECUTOR.with_borrow(|exe| exe.turn(GlobalReactor, timeout))
+}
+
+/// Registers a callback that runs whenever there are no ready tasks, before the thread parks.
+/// Tasks spawned by it are polled before parking, which is useful for maintenance work.
+pub fn on_idle<F>(hook: F)
+where
+    F: Fn() + 'static,
+{
+    EXECUTOR.with_borrow(|exe| exe.on_idle(hook));
+}
+
+/// Registers a callback that runs before the thread blocks waiting for completions
+pub fn on_park<F>(hook: F)
+where
+    F: Fn() + 'static,
+{
+    EXECUTOR.with_borrow(|exe| exe.on_park(hook));
+}
+
+/// Registers a callback that runs after the thread wakes up with completions
+pub fn on_unpark<F>(hook: F)
+where
+    F: Fn() + 'static,
+{
+    EXECUTOR.with_borrow(|exe| exe.on_unpark(hook));
+}
+
+/// Returns the descriptor of the reactor ring on the current thread. It becomes readable
+/// when there are completions to handle, which is when another event loop polling it
+/// should call [run_until_stalled].
+pub fn ring_fd() -> RawFd {
+    with_reactor(|react| react.as_raw_fd())
+}
+
 #[inline]
 pub fn is_done() -> bool {
-    REACTOR.with_borrow(|react| react.is_done())
+    REACTOR.with_borrow(|react| react.as_ref().is_none_or(|react| react.is_done()))
+}
+
+/// Returns the opcodes supported by the kernel, probed when the reactor was created
+pub fn features() -> Features {
+    with_reactor(|react| *react.features())
+}
+
+/// Snapshot of the runtime state on the current thread
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub ring: RingMetrics,
+    pub executor: ExecutorMetrics,
+}
+
+pub fn metrics() -> Metrics {
+    Metrics {
+        ring: with_reactor(|react| react.metrics()),
+        executor: EXECUTOR.with_borrow(|exe| exe.metrics()),
+    }
+}
+
+/// Resources left behind by [shutdown], all of them zero after a clean teardown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Number of tasks aborted by the shutdown
+    pub aborted: usize,
+    /// Number of tasks that never finished, because they wait for completions
+    pub tasks: usize,
+    /// Number of submissions, including cancellations, that never completed
+    pub active: u32,
+    /// Number of detached submissions that never completed
+    pub detached: u32,
+    /// Number of fixed buffers that were never unregistered
+    pub fixed_buffers: u32,
+    /// Number of direct files that were never closed
+    pub direct_files: u32,
+    /// Number of buffer groups that were never released
+    pub buffer_groups: u32,
+}
+
+impl ShutdownReport {
+    /// Returns true if nothing leaked, aborted tasks do not count
+    pub fn is_clean(&self) -> bool {
+        self.tasks == 0
+            && self.active + self.detached == 0
+            && self.fixed_buffers + self.direct_files + self.buffer_groups == 0
+    }
+}
+
+/// Tears down the runtime of the current thread and reports what leaked.
+///
+/// Aborts the remaining tasks and waits up to `timeout` for their cancellations to complete,
+/// then unregisters the fixed buffers and direct files and drops the reactor, so the next
+/// operations use a new one. If submissions are still in flight after the timeout, the reactor
+/// is leaked instead, since the kernel may still write to their buffers.
+///
+/// Must not be called from a task.
+pub fn shutdown(timeout: Duration) -> ShutdownReport {
+    let deadline = Instant::now() + timeout;
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+
+    let (aborted, tasks) = EXECUTOR.with_borrow(|exe| {
+        let aborted = exe.abort_all();
+        while !remaining().is_zero() && exe.turn(GlobalReactor, remaining()) {}
+
+        (aborted, exe.metrics().tasks)
+    });
+
+    let Some(mut ring) = REACTOR.take() else {
+        return ShutdownReport {
+            aborted,
+            tasks,
+            ..Default::default()
+        };
+    };
+
+    // detached submissions, like closing files, are not waited for by any task
+    while ring.active() + ring.metrics().detached > 0 && !remaining().is_zero() {
+        ring.wait_timeout(remaining());
+    }
+
+    let metrics = ring.metrics();
+    let report = ShutdownReport {
+        aborted,
+        tasks,
+        active: metrics.active,
+        detached: metrics.detached,
+        fixed_buffers: metrics.fixed_buffers.used,
+        direct_files: metrics.direct_files.used,
+        buffer_groups: metrics.buffer_groups.used,
+    };
+
+    if let Err(err) = ring.unregister_all() {
+        tracing::warn!(?err, "Failed to unregister ring resources");
+    }
+
+    if report.active + report.detached > 0 {
+        std::mem::forget(ring);
+    }
+
+    if !report.is_clean() {
+        tracing::warn!(?report, "Runtime leaked resources on shutdown");
+    }
+
+    report
 }
 
 #[cfg(feature = "sys")]
@@ -83,4 +327,9 @@ pub mod sys {
     pub const fn reactor() -> crate::GlobalReactor {
         crate::GlobalReactor
     }
+
+    /// Submits all queued operations to the kernel, without waiting for them to complete
+    pub fn flush() {
+        crate::with_reactor(|react| react.flush());
+    }
 }
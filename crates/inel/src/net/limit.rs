@@ -0,0 +1,104 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Waker,
+};
+
+use futures::task::AtomicWaker;
+
+/// Counters for the connections produced by an incoming stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Number of accepted connections that are still open
+    pub active: usize,
+    /// Total number of accepted connections
+    pub accepted: u64,
+    /// Total number of accepts that failed, usually because of exhausted descriptors
+    pub rejected: u64,
+    /// Number of times accepting was paused because of the connection limit
+    pub paused: u64,
+}
+
+struct LimitState {
+    max: usize,
+    active: AtomicUsize,
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    paused: AtomicU64,
+    waker: AtomicWaker,
+}
+
+#[derive(Clone)]
+pub(crate) struct ConnectionLimit {
+    state: Arc<LimitState>,
+}
+
+impl Default for ConnectionLimit {
+    fn default() -> Self {
+        Self::new(usize::MAX)
+    }
+}
+
+impl ConnectionLimit {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            state: Arc::new(LimitState {
+                max,
+                active: AtomicUsize::new(0),
+                accepted: AtomicU64::new(0),
+                rejected: AtomicU64::new(0),
+                paused: AtomicU64::new(0),
+                waker: AtomicWaker::new(),
+            }),
+        }
+    }
+
+    pub(crate) fn is_reached(&self) -> bool {
+        self.state.active.load(Ordering::Acquire) >= self.state.max
+    }
+
+    /// Registers a waker to be called when a connection is closed
+    pub(crate) fn register(&self, waker: &Waker) {
+        self.state.waker.register(waker);
+    }
+
+    pub(crate) fn acquire(&self) -> ConnectionGuard {
+        self.state.active.fetch_add(1, Ordering::AcqRel);
+        self.state.accepted.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard {
+            state: self.state.clone(),
+        }
+    }
+
+    pub(crate) fn reject(&self) {
+        self.state.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn pause(&self) {
+        self.state.paused.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            active: self.state.active.load(Ordering::Acquire),
+            accepted: self.state.accepted.load(Ordering::Relaxed),
+            rejected: self.state.rejected.load(Ordering::Relaxed),
+            paused: self.state.paused.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Held by every accepted connection, releases its place when dropped
+pub(crate) struct ConnectionGuard {
+    state: Arc<LimitState>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        if self.state.active.fetch_sub(1, Ordering::AcqRel) == self.state.max {
+            self.state.waker.wake();
+        }
+    }
+}
@@ -1,3 +1,7 @@
+mod limit;
+mod options;
 mod tcp;
 
+pub use limit::ConnectionStats;
+pub use options::TcpKeepalive;
 pub use tcp::*;
@@ -0,0 +1,194 @@
+use std::{io::Result, time::Duration};
+
+use inel_reactor::op::{self, OpExt, SockOpt};
+
+use crate::{
+    source::{OwnedDirect, OwnedFd},
+    GlobalReactor,
+};
+
+/// Parameters for tcp keepalive probes, unset values keep the system defaults
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TcpKeepalive {
+    idle: Option<Duration>,
+    interval: Option<Duration>,
+    count: Option<u32>,
+}
+
+impl TcpKeepalive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the idle time before the first probe is sent
+    pub fn idle(mut self, idle: Duration) -> Self {
+        self.idle = Some(idle);
+        self
+    }
+
+    /// Sets the time between probes
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Sets the number of unanswered probes before dropping the connection
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub(crate) fn options(keepalive: Option<Self>) -> impl Iterator<Item = SockOpt> {
+        let params = keepalive.unwrap_or_default();
+
+        std::iter::once(SockOpt::keepalive(keepalive.is_some()))
+            .chain(params.idle.map(SockOpt::keepalive_idle))
+            .chain(params.interval.map(SockOpt::keepalive_interval))
+            .chain(params.count.map(SockOpt::keepalive_count))
+    }
+}
+
+/// Sets socket options through the ring, falling back to `setsockopt`
+/// on kernels that do not support `SOCKET_URING_OP_SETSOCKOPT`
+pub(crate) trait SetOption {
+    async fn set_option(&self, opt: SockOpt) -> Result<()>;
+
+    async fn set_options(&self, opts: impl IntoIterator<Item = SockOpt>) -> Result<()> {
+        for opt in opts {
+            self.set_option(opt).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if the ring can not set socket options, probed when the reactor was created
+pub(crate) fn is_probed_unsupported() -> bool {
+    !crate::features().setsockopt()
+}
+
+/// Returns true if the kernel supports uring commands, but not on sockets (before linux 6.7)
+pub(crate) fn is_unsupported(err: &std::io::Error) -> bool {
+    err.raw_os_error() == Some(libc::EOPNOTSUPP)
+}
+
+impl SetOption for OwnedFd {
+    async fn set_option(&self, opt: SockOpt) -> Result<()> {
+        if is_probed_unsupported() {
+            return opt.apply(self.as_raw());
+        }
+
+        match op::SetSockOpt::new(self, opt).run_on(GlobalReactor).await {
+            Err(err) if is_unsupported(&err) => opt.apply(self.as_raw()),
+            res => res,
+        }
+    }
+}
+
+impl SetOption for OwnedDirect {
+    async fn set_option(&self, opt: SockOpt) -> Result<()> {
+        self.set_options([opt]).await
+    }
+
+    /// Options the ring can not set are applied through a single installed descriptor
+    async fn set_options(&self, opts: impl IntoIterator<Item = SockOpt>) -> Result<()> {
+        let mut opts = opts.into_iter().peekable();
+
+        if !is_probed_unsupported() {
+            while let Some(opt) = opts.peek() {
+                match op::SetSockOpt::new(self, *opt).run_on(GlobalReactor).await {
+                    Err(err) if is_unsupported(&err) => break,
+                    res => res?,
+                }
+                opts.next();
+            }
+        }
+
+        if opts.peek().is_none() {
+            return Ok(());
+        }
+
+        let fd = op::InstallSlot::new(self).run_on(GlobalReactor).await?;
+        let fd = OwnedFd::from_raw(fd);
+        opts.try_for_each(|opt| opt.apply(fd.as_raw()))
+    }
+}
+
+/// Adds the socket option setters to a type holding its socket as an [OwnedFd] or
+/// [OwnedDirect] in `$field`. The tcp types are the only sockets in [crate::net], so
+/// only the generic and tcp options are covered.
+macro_rules! impl_socket_options {
+    ($type:ty, $field:ident) => {
+        impl $type {
+            /// Sets the `SO_LINGER` option
+            pub async fn set_linger(&self, linger: Option<std::time::Duration>) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::linger(linger);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Sets the `SO_REUSEADDR` option
+            pub async fn set_reuseaddr(&self, reuseaddr: bool) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::reuseaddr(reuseaddr);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Sets the `SO_REUSEPORT` option
+            pub async fn set_reuseport(&self, reuseport: bool) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::reuseport(reuseport);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Sets the `SO_RCVBUF` option
+            pub async fn set_recv_buffer_size(&self, size: usize) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::recv_buffer_size(size);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Sets the `SO_SNDBUF` option
+            pub async fn set_send_buffer_size(&self, size: usize) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::send_buffer_size(size);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Sets the `IP_TTL` option
+            pub async fn set_ttl(&self, ttl: u32) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::ttl(ttl);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+        }
+    };
+
+    (tcp $type:ty, $field:ident) => {
+        $crate::net::options::impl_socket_options!($type, $field);
+
+        impl $type {
+            /// Sets the `TCP_NODELAY` option
+            pub async fn set_nodelay(&self, nodelay: bool) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::nodelay(nodelay);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+
+            /// Enables keepalive probes with the given parameters, or disables them
+            pub async fn set_keepalive(
+                &self,
+                keepalive: Option<$crate::net::TcpKeepalive>,
+            ) -> Result<()> {
+                let opts = $crate::net::TcpKeepalive::options(keepalive);
+                $crate::net::options::SetOption::set_options(&self.$field, opts).await
+            }
+        }
+    };
+
+    (tcp_listener $type:ty, $field:ident) => {
+        $crate::net::options::impl_socket_options!(tcp $type, $field);
+
+        impl $type {
+            /// Sets the `TCP_FASTOPEN` option, with the maximum length of pending syns
+            pub async fn set_fastopen(&self, queue: u32) -> Result<()> {
+                let opt = inel_reactor::op::SockOpt::fastopen(queue);
+                $crate::net::options::SetOption::set_option(&self.$field, opt).await
+            }
+        }
+    };
+}
+
+pub(crate) use impl_socket_options;
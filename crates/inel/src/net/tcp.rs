@@ -5,19 +5,28 @@ use std::{
     net::{Shutdown, SocketAddr, ToSocketAddrs},
     os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
+    time::Duration,
 };
 
-use futures::{Stream, StreamExt};
+use futures::{future::LocalBoxFuture, FutureExt, Stream, StreamExt};
+use inel_interface::coop;
 use inel_reactor::{
-    op::{self, AcceptMulti, AcceptMultiAuto, DetachOp, OpExt},
-    source::{AsSource, Source},
+    op::{
+        self, AcceptBacklog, AcceptMulti, AcceptMultiAuto, DetachOp, MultiOp, Op, OpExt, SockOpt,
+    },
+    source::{AsSource, DirectAutoFd, Source},
     submission::Submission,
     util,
 };
 
 use crate::{
     io::{ReadSource, WriteSource},
+    net::{
+        limit::{ConnectionGuard, ConnectionLimit},
+        options::{self, impl_socket_options, SetOption},
+        ConnectionStats, TcpKeepalive,
+    },
     source::{OwnedDirect, OwnedFd},
     GlobalReactor,
 };
@@ -46,6 +55,363 @@ where
     )))
 }
 
+/// How incoming connections are distributed between sharded listeners
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Steering {
+    /// Let the kernel pick a listener based on the connection hash
+    #[default]
+    Hash,
+    /// Pick the listener based on the cpu that handles the connection,
+    /// cpu `i` will be steered to shard `i % shards`.
+    ///
+    /// Connections are only handled on the cpu of their shard if the thread driving
+    /// shard `i` is pinned to cpu `i`, otherwise this is no better than [Steering::Hash].
+    Cpu,
+}
+
+fn attach_cpu_steering(sock: RawFd, shards: usize) -> Result<()> {
+    let code = [
+        libc::sock_filter {
+            code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16,
+            jt: 0,
+            jf: 0,
+            k: (libc::SKF_AD_OFF + libc::SKF_AD_CPU) as u32,
+        },
+        libc::sock_filter {
+            code: (libc::BPF_ALU | libc::BPF_MOD | libc::BPF_K) as u16,
+            jt: 0,
+            jf: 0,
+            k: shards as u32,
+        },
+        libc::sock_filter {
+            code: (libc::BPF_RET | libc::BPF_A) as u16,
+            jt: 0,
+            jf: 0,
+            k: 0,
+        },
+    ];
+
+    let prog = libc::sock_fprog {
+        len: code.len() as u16,
+        filter: code.as_ptr() as *mut _,
+    };
+
+    util::setsockopt(
+        sock,
+        libc::SOL_SOCKET,
+        libc::SO_ATTACH_REUSEPORT_CBPF,
+        &prog,
+    )
+}
+
+/// Builder for configuring a tcp socket before it is connected or listening.
+///
+/// No socket is created until [TcpSocket::connect] or [TcpSocket::listen] is called,
+/// the options are applied right after creation, before binding.
+#[derive(Clone)]
+pub struct TcpSocket {
+    domain: i32,
+    options: Vec<SockOpt>,
+    local: Option<SocketAddr>,
+    backlog: u32,
+}
+
+impl TcpSocket {
+    pub fn new_v4() -> Self {
+        Self::new(libc::AF_INET)
+    }
+
+    pub fn new_v6() -> Self {
+        Self::new(libc::AF_INET6)
+    }
+
+    fn new(domain: i32) -> Self {
+        Self {
+            domain,
+            options: Vec::new(),
+            local: None,
+            backlog: DEFAULT_LISTEN_BACKLOG,
+        }
+    }
+
+    fn from_addr(addr: &SocketAddr) -> Self {
+        if addr.is_ipv4() {
+            Self::new_v4()
+        } else {
+            Self::new_v6()
+        }
+    }
+
+    fn option(mut self, opt: SockOpt) -> Self {
+        self.options.push(opt);
+        self
+    }
+
+    /// Sets the local address to bind to, before connecting or listening
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.local = Some(addr);
+        self
+    }
+
+    /// Sets the maximum number of pending connections when listening
+    pub fn backlog(mut self, backlog: u32) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Sets the `TCP_NODELAY` option
+    pub fn nodelay(self, nodelay: bool) -> Self {
+        self.option(SockOpt::nodelay(nodelay))
+    }
+
+    /// Enables keepalive probes with the given parameters, or disables them
+    pub fn keepalive(self, keepalive: Option<TcpKeepalive>) -> Self {
+        TcpKeepalive::options(keepalive).fold(self, Self::option)
+    }
+
+    /// Sets the `SO_LINGER` option
+    pub fn linger(self, linger: Option<Duration>) -> Self {
+        self.option(SockOpt::linger(linger))
+    }
+
+    /// Sets the `SO_REUSEADDR` option
+    pub fn reuseaddr(self, reuseaddr: bool) -> Self {
+        self.option(SockOpt::reuseaddr(reuseaddr))
+    }
+
+    /// Sets the `SO_REUSEPORT` option
+    pub fn reuseport(self, reuseport: bool) -> Self {
+        self.option(SockOpt::reuseport(reuseport))
+    }
+
+    /// Sets the `SO_RCVBUF` option
+    pub fn recv_buffer_size(self, size: usize) -> Self {
+        self.option(SockOpt::recv_buffer_size(size))
+    }
+
+    /// Sets the `SO_SNDBUF` option
+    pub fn send_buffer_size(self, size: usize) -> Self {
+        self.option(SockOpt::send_buffer_size(size))
+    }
+
+    /// Sets the `IP_TTL` option
+    pub fn ttl(self, ttl: u32) -> Self {
+        self.option(SockOpt::ttl(ttl))
+    }
+
+    /// Sets the `TCP_FASTOPEN` option, with the maximum length of pending syns
+    pub fn fastopen(self, queue: u32) -> Self {
+        self.option(SockOpt::fastopen(queue))
+    }
+
+    /// Sets the `TCP_FASTOPEN_CONNECT` option
+    pub fn fastopen_connect(self, fastopen: bool) -> Self {
+        self.option(SockOpt::fastopen_connect(fastopen))
+    }
+
+    /// Creates the socket as a direct descriptor
+    pub fn direct(self) -> DirectTcpSocket {
+        DirectTcpSocket { inner: self }
+    }
+
+    async fn create(&self) -> Result<OwnedFd> {
+        let sock = op::Socket::new(self.domain, libc::SOCK_STREAM)
+            .run_on(GlobalReactor)
+            .await?;
+
+        let sock = OwnedFd::from_raw(sock);
+
+        sock.set_options(self.options.iter().copied()).await?;
+
+        if let Some(addr) = self.local {
+            op::Bind::new(&sock, addr).run_on(GlobalReactor).await?;
+        }
+
+        Ok(sock)
+    }
+
+    pub async fn connect(self, addr: SocketAddr) -> Result<TcpStream> {
+        let sock = self.create().await?;
+
+        op::Connect::new(&sock, addr).run_on(GlobalReactor).await?;
+
+        Ok(TcpStream { sock, _guard: None })
+    }
+
+    pub async fn listen(self) -> Result<TcpListener> {
+        let sock = self.create().await?;
+
+        op::Listen::new(&sock, self.backlog)
+            .run_on(GlobalReactor)
+            .await?;
+
+        Ok(TcpListener { sock })
+    }
+
+    /// Creates `shards` listeners bound to the same address with `SO_REUSEPORT`.
+    ///
+    /// The listeners can be moved to other threads, so each one is driven by its own ring.
+    /// If the bind address has port 0, all the listeners will share the first assigned port.
+    pub async fn listen_sharded(
+        self,
+        shards: usize,
+        steering: Steering,
+    ) -> Result<Vec<TcpListener>> {
+        if shards == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "at least one shard is required",
+            ));
+        }
+
+        let socket = self.reuseport(true);
+        let first = socket.clone().listen().await?;
+
+        let socket = socket.bind(first.local_addr()?);
+        let mut listeners = vec![first];
+        for _ in 1..shards {
+            listeners.push(socket.clone().listen().await?);
+        }
+
+        if steering == Steering::Cpu {
+            attach_cpu_steering(listeners[0].sock.as_raw(), shards)?;
+        }
+
+        Ok(listeners)
+    }
+}
+
+/// A [TcpSocket] that will be created as a direct descriptor.
+///
+/// The options, bind and connect/listen are linked together and submitted at once.
+#[derive(Clone)]
+pub struct DirectTcpSocket {
+    inner: TcpSocket,
+}
+
+impl DirectTcpSocket {
+    fn setup<'a, Op>(
+        &'a self,
+        direct: &'a OwnedDirect,
+        options: &'a [SockOpt],
+        last: Op,
+    ) -> Vec<LocalBoxFuture<'a, Result<()>>>
+    where
+        Op: op::Op<Output = Result<()>> + 'a,
+    {
+        let options = options.iter().map(|opt| {
+            op::SetSockOpt::new(direct, *opt)
+                .chain()
+                .run_on(GlobalReactor)
+                .boxed_local()
+        });
+
+        let bind = self.inner.local.map(|addr| {
+            op::Bind::new(direct, addr)
+                .chain()
+                .run_on(GlobalReactor)
+                .boxed_local()
+        });
+
+        options
+            .chain(bind)
+            .chain(std::iter::once(last.run_on(GlobalReactor).boxed_local()))
+            .collect()
+    }
+
+    /// Returns false if the ring can not set the options, so the socket has to be created
+    /// before setting them and they can not be linked with the rest of the setup
+    fn links_options(&self) -> bool {
+        self.inner.options.is_empty() || !options::is_probed_unsupported()
+    }
+
+    /// Runs the linked setup, after the socket if it is linked as well. On kernels that do not
+    /// support socket options through the ring, they are set like [SetOption] does and the rest
+    /// of the chain is retried without them.
+    async fn run<'a, Op>(
+        &'a self,
+        direct: &'a OwnedDirect,
+        socket: Option<LocalBoxFuture<'a, Result<()>>>,
+        last: impl Fn() -> Op,
+    ) -> Result<()>
+    where
+        Op: op::Op<Output = Result<()>> + 'a,
+    {
+        if !self.links_options() {
+            return self.fallback(direct, last()).await;
+        }
+
+        let linked = socket.is_some();
+
+        let mut ops = Vec::from_iter(socket);
+        ops.extend(self.setup(direct, &self.inner.options, last()));
+
+        let mut results = futures::future::join_all(ops).await.into_iter();
+        if linked {
+            results.next().unwrap()?;
+        }
+
+        // the entries after a failed one are canceled, so only the failure of an option
+        // itself tells that the kernel does not support them
+        let opts = results
+            .by_ref()
+            .take(self.inner.options.len())
+            .collect::<Result<()>>();
+        let rest = results.collect::<Result<()>>();
+
+        match opts {
+            Err(err) if options::is_unsupported(&err) => self.fallback(direct, last()).await,
+            opts => opts.and(rest),
+        }
+    }
+
+    /// Sets the options like [SetOption] does, then runs the rest of the setup linked
+    async fn fallback<'a, Op>(&'a self, direct: &'a OwnedDirect, last: Op) -> Result<()>
+    where
+        Op: op::Op<Output = Result<()>> + 'a,
+    {
+        direct
+            .set_options(self.inner.options.iter().copied())
+            .await?;
+
+        crate::util::chain_all(self.setup(direct, &[], last)).await
+    }
+
+    pub async fn connect(self, addr: SocketAddr) -> Result<DirectTcpStream> {
+        let slot = op::Socket::new(self.inner.domain, libc::SOCK_STREAM)
+            .direct()
+            .run_on(GlobalReactor)
+            .await?;
+
+        let direct = OwnedDirect::auto(slot);
+
+        self.run(&direct, None, || op::Connect::new(&direct, addr))
+            .await?;
+
+        Ok(DirectTcpStream::from_direct(direct))
+    }
+
+    pub async fn listen(self) -> Result<DirectTcpListener> {
+        let direct = OwnedDirect::reserve()?;
+
+        let socket = op::Socket::new(self.inner.domain, libc::SOCK_STREAM).fixed(&direct);
+
+        let socket = if self.links_options() {
+            Some(socket.chain().run_on(GlobalReactor).boxed_local())
+        } else {
+            socket.run_on(GlobalReactor).await?;
+            None
+        };
+
+        self.run(&direct, socket, || {
+            op::Listen::new(&direct, self.inner.backlog)
+        })
+        .await?;
+
+        Ok(DirectTcpListener { direct })
+    }
+}
+
 pub struct TcpListener {
     sock: OwnedFd,
 }
@@ -61,42 +427,40 @@ impl TcpListener {
     where
         A: ToSocketAddrs,
     {
-        for_each_addr(addr, |addr| async move {
-            let sock = op::Socket::stream_from_addr(&addr)
-                .run_on(GlobalReactor)
-                .await?;
-
-            let sock = OwnedFd::from_raw(sock);
-
-            op::Bind::new(&sock, addr).run_on(GlobalReactor).await?;
-
-            op::Listen::new(&sock, DEFAULT_LISTEN_BACKLOG)
-                .run_on(GlobalReactor)
-                .await?;
+        for_each_addr(addr, |addr| TcpSocket::from_addr(&addr).bind(addr).listen()).await
+    }
 
-            Ok(Self { sock })
+    pub async fn bind_direct<A>(addr: A) -> Result<DirectTcpListener>
+    where
+        A: ToSocketAddrs,
+    {
+        for_each_addr(addr, |addr| {
+            TcpSocket::from_addr(&addr).bind(addr).direct().listen()
         })
         .await
     }
 
-    pub async fn bind_direct<A>(addr: A) -> Result<DirectTcpListener>
+    /// Binds `shards` listeners to the same address with `SO_REUSEPORT`,
+    /// see [TcpSocket::listen_sharded].
+    ///
+    /// Each [Incoming] submits its accept on the ring of the thread that first polls it,
+    /// so they should be moved to their worker threads before being polled.
+    pub async fn bind_sharded<A>(
+        addr: A,
+        shards: usize,
+        steering: Steering,
+    ) -> Result<Vec<Incoming>>
     where
         A: ToSocketAddrs,
     {
-        for_each_addr(addr, |addr| async move {
-            let direct = OwnedDirect::reserve().unwrap();
-
-            let socket = op::Socket::stream_from_addr(&addr)
-                .fixed(&direct)
-                .run_on(GlobalReactor);
-            let bind = op::Bind::new(&direct, addr).chain().run_on(GlobalReactor);
-            let listen = op::Listen::new(&direct, DEFAULT_LISTEN_BACKLOG).run_on(GlobalReactor);
-
-            crate::util::chain3(socket, bind, listen).await?;
-
-            Ok(DirectTcpListener { direct })
+        let listeners = for_each_addr(addr, |addr| {
+            TcpSocket::from_addr(&addr)
+                .bind(addr)
+                .listen_sharded(shards, steering)
         })
-        .await
+        .await?;
+
+        Ok(listeners.into_iter().map(Incoming::new).collect())
     }
 
     pub fn local_addr(&self) -> Result<SocketAddr> {
@@ -134,16 +498,174 @@ impl FromRawFd for TcpListener {
     }
 }
 
+impl_socket_options!(tcp_listener TcpListener, sock);
+
+trait MultiAccept: MultiOp<Output = Result<Self::Conn>> {
+    type Conn;
+    type Single: Op<Output = Result<(Self::Conn, SocketAddr)>>;
+
+    fn accept(source: &Source, backlog: &AcceptBacklog) -> Self;
+
+    fn accept_single(source: &Source) -> Self::Single;
+
+    fn pop(backlog: &AcceptBacklog) -> Option<Self::Conn>;
+}
+
+impl MultiAccept for AcceptMulti {
+    type Conn = RawFd;
+    type Single = op::Accept;
+
+    fn accept(source: &Source, backlog: &AcceptBacklog) -> Self {
+        AcceptMulti::new(source).backlog(backlog)
+    }
+
+    fn accept_single(source: &Source) -> Self::Single {
+        op::Accept::new(source)
+    }
+
+    fn pop(backlog: &AcceptBacklog) -> Option<Self::Conn> {
+        backlog.pop_fd()
+    }
+}
+
+impl MultiAccept for AcceptMultiAuto {
+    type Conn = DirectAutoFd;
+    type Single = op::AcceptAuto;
+
+    fn accept(source: &Source, backlog: &AcceptBacklog) -> Self {
+        AcceptMulti::new(source).backlog(backlog).direct()
+    }
+
+    fn accept_single(source: &Source) -> Self::Single {
+        op::Accept::new(source).direct()
+    }
+
+    fn pop(backlog: &AcceptBacklog) -> Option<Self::Conn> {
+        backlog.pop_direct()
+    }
+}
+
+type Accepted<C> = Result<(C, ConnectionGuard)>;
+
+enum AcceptStream<O: MultiAccept> {
+    Multi(Submission<O, GlobalReactor>),
+    Single(Submission<O::Single, GlobalReactor>),
+}
+
+/// Drives a multishot accept, which is cancelled while the connection limit is reached
+/// and submitted again once connections are closed. Falls back to submitting single
+/// accepts on kernels without multishot accept, which are kept pending at the limit.
+struct Accepting<O: MultiAccept> {
+    source: Source,
+    stream: Option<AcceptStream<O>>,
+    backlog: AcceptBacklog,
+    limit: ConnectionLimit,
+    multishot: bool,
+    /// A single accept is pending while the connection limit is reached
+    parked: bool,
+}
+
+impl<O: MultiAccept> Accepting<O> {
+    fn new(source: &impl AsSource) -> Self {
+        Self {
+            source: source.as_source(),
+            stream: None,
+            backlog: AcceptBacklog::new(),
+            limit: ConnectionLimit::default(),
+            multishot: true,
+            parked: false,
+        }
+    }
+
+    fn submit(&self) -> AcceptStream<O> {
+        if self.multishot && crate::features().multishot_accept() {
+            AcceptStream::Multi(O::accept(&self.source, &self.backlog).run_on(GlobalReactor))
+        } else {
+            AcceptStream::Single(O::accept_single(&self.source).run_on(GlobalReactor))
+        }
+    }
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Option<Accepted<O::Conn>>> {
+        self.limit.register(cx.waker());
+
+        if self.limit.is_reached() {
+            match self.stream {
+                Some(AcceptStream::Multi(_)) => {
+                    self.stream = None;
+                    self.limit.pause();
+                }
+                // dropping it could lose a connection already accepted by the kernel,
+                // so its result waits in the ring until there is room
+                Some(AcceptStream::Single(_)) if !self.parked => {
+                    self.parked = true;
+                    self.limit.pause();
+                }
+                _ => {}
+            }
+            return Poll::Pending;
+        }
+
+        self.parked = false;
+
+        ready!(coop::poll_proceed(cx));
+
+        if let Some(conn) = O::pop(&self.backlog) {
+            coop::consume();
+            return Poll::Ready(Some(Ok((conn, self.limit.acquire()))));
+        }
+
+        if self.stream.is_none() {
+            self.stream = Some(self.submit());
+        }
+
+        let next = match self.stream.as_mut().unwrap() {
+            AcceptStream::Multi(stream) => ready!(stream.poll_next_unpin(cx)),
+            AcceptStream::Single(accept) => {
+                let res = ready!(accept.poll_unpin(cx));
+                self.stream = None;
+                Some(res.map(|(conn, _)| conn))
+            }
+        };
+
+        Poll::Ready(match next {
+            Some(Ok(conn)) => Some(Ok((conn, self.limit.acquire()))),
+            Some(Err(err)) => {
+                self.limit.reject();
+                Some(Err(err))
+            }
+            None => None,
+        })
+    }
+}
+
 pub struct Incoming {
     #[allow(dead_code)]
     listener: TcpListener,
-    stream: Submission<AcceptMulti, GlobalReactor>,
+    accept: Accepting<AcceptMulti>,
 }
 
 impl Incoming {
     pub fn new(listener: TcpListener) -> Self {
-        let stream = AcceptMulti::new(&listener.sock).run_on(GlobalReactor);
-        Self { listener, stream }
+        let accept = Accepting::new(&listener.sock);
+        Self { listener, accept }
+    }
+
+    /// Pauses accepting while there are `max` open connections from this stream,
+    /// resuming when they are dropped. Should be set before the first poll.
+    pub fn with_max_connections(mut self, max: usize) -> Self {
+        self.accept.limit = ConnectionLimit::new(max);
+        self
+    }
+
+    /// Uses a single multishot accept when supported by the kernel, which is the default,
+    /// otherwise an accept is submitted for each connection.
+    pub fn with_multishot(mut self, multishot: bool) -> Self {
+        self.accept.multishot = multishot;
+        self
+    }
+
+    pub fn stats(&self) -> ConnectionStats {
+        self.accept.limit.stats()
     }
 }
 
@@ -151,26 +673,45 @@ impl Stream for Incoming {
     type Item = Result<TcpStream>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.get_mut()
-            .stream
-            .poll_next_unpin(cx)
-            .map(|next| next.map(|res| res.map(|sock| unsafe { TcpStream::from_raw_fd(sock) })))
+        self.get_mut().accept.poll_accept(cx).map(|next| {
+            next.map(|res| {
+                res.map(|(sock, guard)| TcpStream {
+                    sock: OwnedFd::from_raw(sock),
+                    _guard: Some(guard),
+                })
+            })
+        })
     }
 }
 
 pub struct DirectIncoming {
     #[allow(dead_code)]
     listener: DirectTcpListener,
-    stream: Submission<AcceptMultiAuto, GlobalReactor>,
+    accept: Accepting<AcceptMultiAuto>,
 }
 
 impl DirectIncoming {
     pub fn new(listener: DirectTcpListener) -> Self {
-        let stream = AcceptMulti::new(&listener.direct)
-            .direct()
-            .run_on(GlobalReactor);
+        let accept = Accepting::new(&listener.direct);
+        Self { listener, accept }
+    }
 
-        Self { listener, stream }
+    /// Pauses accepting while there are `max` open connections from this stream,
+    /// resuming when they are dropped. Should be set before the first poll.
+    pub fn with_max_connections(mut self, max: usize) -> Self {
+        self.accept.limit = ConnectionLimit::new(max);
+        self
+    }
+
+    /// Uses a single multishot accept when supported by the kernel, which is the default,
+    /// otherwise an accept is submitted for each connection.
+    pub fn with_multishot(mut self, multishot: bool) -> Self {
+        self.accept.multishot = multishot;
+        self
+    }
+
+    pub fn stats(&self) -> ConnectionStats {
+        self.accept.limit.stats()
     }
 }
 
@@ -178,14 +719,20 @@ impl Stream for DirectIncoming {
     type Item = Result<DirectTcpStream>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.get_mut().stream.poll_next_unpin(cx).map(|next| {
-            next.map(|res| res.map(|slot| DirectTcpStream::from_direct(OwnedDirect::auto(slot))))
+        self.get_mut().accept.poll_accept(cx).map(|next| {
+            next.map(|res| {
+                res.map(|(slot, guard)| DirectTcpStream {
+                    direct: OwnedDirect::auto(slot),
+                    _guard: Some(guard),
+                })
+            })
         })
     }
 }
 
 pub struct TcpStream {
     sock: OwnedFd,
+    _guard: Option<ConnectionGuard>,
 }
 
 impl Debug for TcpStream {
@@ -211,37 +758,15 @@ impl TcpStream {
     where
         A: ToSocketAddrs,
     {
-        for_each_addr(addr, |addr| async move {
-            let sock = op::Socket::stream_from_addr(&addr)
-                .run_on(GlobalReactor)
-                .await?;
-
-            let sock = OwnedFd::from_raw(sock);
-
-            op::Connect::new(&sock, addr).run_on(GlobalReactor).await?;
-
-            Ok(Self { sock })
-        })
-        .await
+        for_each_addr(addr, |addr| TcpSocket::from_addr(&addr).connect(addr)).await
     }
 
     pub async fn connect_direct<A>(addr: A) -> Result<DirectTcpStream>
     where
         A: ToSocketAddrs,
     {
-        for_each_addr(addr, |addr| async move {
-            let slot = op::Socket::stream_from_addr(&addr)
-                .direct()
-                .run_on(GlobalReactor)
-                .await?;
-
-            let direct = OwnedDirect::auto(slot);
-
-            op::Connect::new(&direct, addr)
-                .run_on(GlobalReactor)
-                .await?;
-
-            Ok(DirectTcpStream::from_direct(direct))
+        for_each_addr(addr, |addr| {
+            TcpSocket::from_addr(&addr).direct().connect(addr)
         })
         .await
     }
@@ -269,6 +794,8 @@ impl TcpStream {
     }
 }
 
+impl_socket_options!(tcp TcpStream, sock);
+
 impl AsRawFd for TcpStream {
     fn as_raw_fd(&self) -> RawFd {
         self.sock.as_raw()
@@ -285,6 +812,7 @@ impl FromRawFd for TcpStream {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
         Self {
             sock: OwnedFd::from_raw(fd),
+            _guard: None,
         }
     }
 }
@@ -316,8 +844,11 @@ impl DirectTcpListener {
     }
 }
 
+impl_socket_options!(tcp_listener DirectTcpListener, direct);
+
 pub struct DirectTcpStream {
     direct: OwnedDirect,
+    _guard: Option<ConnectionGuard>,
 }
 
 impl Debug for DirectTcpStream {
@@ -340,7 +871,10 @@ impl WriteSource for DirectTcpStream {
 
 impl DirectTcpStream {
     fn from_direct(direct: OwnedDirect) -> Self {
-        Self { direct }
+        Self {
+            direct,
+            _guard: None,
+        }
     }
 
     pub async fn shutdown(&self, how: Shutdown) -> Result<()> {
@@ -358,6 +892,8 @@ impl DirectTcpStream {
     }
 }
 
+impl_socket_options!(tcp DirectTcpStream, direct);
+
 impl Drop for DirectTcpStream {
     // NOTE: this is needed on kernel 6.11 to properly close the connection
     //       on kernel 6.15, a close is sufficient
@@ -0,0 +1,169 @@
+use std::{cell::RefCell, future::Future, io::Result, time::Duration};
+
+use inel_executor::{Executor, JoinHandle, Priority};
+use inel_reactor::ring::{Ring, RingOptions};
+use tracing::warn;
+
+use crate::{ShutdownReport, EXECUTOR, REACTOR};
+
+/// Time a dropped [Runtime] waits for the cancellations of its aborted tasks
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Parts {
+    executor: Executor,
+    reactor: Option<Ring>,
+}
+
+impl Parts {
+    /// Installs these parts on the current thread, returning the ones that were replaced
+    fn swap(self) -> Self {
+        Self {
+            executor: EXECUTOR.replace(self.executor),
+            reactor: REACTOR.replace(self.reactor),
+        }
+    }
+}
+
+/// An executor and reactor pair, independent of the ones used by the global functions.
+///
+/// Entering a runtime installs it on the current thread, so all the operations
+/// and tasks created while entered use it.
+pub struct Runtime {
+    parts: RefCell<Option<Parts>>,
+}
+
+impl Runtime {
+    pub fn new(options: RingOptions) -> Result<Self> {
+        Ok(Self {
+            parts: RefCell::new(Some(Parts {
+                executor: Executor::new(),
+                reactor: Some(options.try_build()?),
+            })),
+        })
+    }
+
+    /// Installs the runtime on the current thread, until the returned guard is dropped.
+    /// Entering an already entered runtime does nothing.
+    ///
+    /// # Panics
+    /// If called from a task running on another runtime.
+    pub fn enter(&self) -> EnterGuard<'_> {
+        let previous = self.parts.borrow_mut().take().map(Parts::swap);
+
+        EnterGuard {
+            runtime: self,
+            previous,
+        }
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        let _guard = self.enter();
+        crate::spawn(future)
+    }
+
+    pub fn spawn_with_priority<F>(&self, priority: Priority, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        let _guard = self.enter();
+        crate::spawn_with_priority(priority, future)
+    }
+
+    /// Runs the future and all spawned tasks to completion.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future + 'static,
+    {
+        let _guard = self.enter();
+        crate::block_on(future)
+    }
+
+    /// Runs all spawned tasks to completion.
+    pub fn run(&self) {
+        let _guard = self.enter();
+        crate::run();
+    }
+
+    /// Runs tasks until none can make progress without blocking, see [crate::run_until_stalled]
+    pub fn run_until_stalled(&self) -> bool {
+        let _guard = self.enter();
+        crate::run_until_stalled()
+    }
+
+    /// Runs a single batch of tasks, see [crate::turn]
+    pub fn turn(&self, timeout: Duration) -> bool {
+        let _guard = self.enter();
+        crate::turn(timeout)
+    }
+
+    pub fn is_done(&self) -> bool {
+        let _guard = self.enter();
+        crate::is_done()
+    }
+
+    /// Aborts the remaining tasks and tears the runtime down, see [crate::shutdown]
+    pub fn shutdown(self, timeout: Duration) -> ShutdownReport {
+        let _guard = self.enter();
+        crate::shutdown(timeout)
+    }
+
+    /// Runs the remaining tasks to completion and waits for all submissions, including detached
+    /// ones, to complete. Returns false if resources are still held, see [Runtime::is_done].
+    ///
+    /// Never returns while a task runs forever, unlike dropping the runtime which aborts them.
+    pub fn drain(&self) -> bool {
+        let _guard = self.enter();
+
+        // already torn down by a shutdown
+        if REACTOR.with_borrow(Option::is_none) {
+            return true;
+        }
+
+        crate::run();
+
+        crate::with_reactor(|ring| {
+            while !ring.is_done() {
+                let metrics = ring.metrics();
+                if metrics.active + metrics.detached == 0 {
+                    warn!(?metrics, "Runtime holds leaked resources");
+                    return false;
+                }
+
+                ring.wait();
+            }
+
+            true
+        })
+    }
+}
+
+/// Aborts the remaining tasks and waits up to [SHUTDOWN_TIMEOUT] for their cancellations
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        let _guard = self.enter();
+
+        // already torn down by a shutdown
+        if REACTOR.with_borrow(Option::is_none) {
+            return;
+        }
+
+        crate::shutdown(SHUTDOWN_TIMEOUT);
+    }
+}
+
+/// Keeps a [Runtime] installed on the current thread, restoring the previous one when dropped
+pub struct EnterGuard<'a> {
+    runtime: &'a Runtime,
+    previous: Option<Parts>,
+}
+
+impl Drop for EnterGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            *self.runtime.parts.borrow_mut() = Some(previous.swap());
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use inel_reactor::op::{self, OpExt};
+
+use crate::GlobalReactor;
+
+/// How often waiting tasks check the value on kernels without futex operations
+const FALLBACK_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A 32 bit value that tasks on any thread can wait on.
+///
+/// Waiting submits a futex wait to the ring of the task, so the thread keeps running
+/// other tasks. Changing the value through the cell wakes the waiting tasks with a
+/// `futex` call, which also works on threads without a runtime.
+#[derive(Debug, Default)]
+pub struct AtomicWaitCell {
+    word: Arc<AtomicU32>,
+}
+
+impl AtomicWaitCell {
+    pub fn new(value: u32) -> Self {
+        Self {
+            word: Arc::new(AtomicU32::new(value)),
+        }
+    }
+
+    pub fn load(&self) -> u32 {
+        self.word.load(Ordering::Acquire)
+    }
+
+    pub fn store(&self, value: u32) {
+        self.word.store(value, Ordering::Release);
+        self.wake_all();
+    }
+
+    pub fn swap(&self, value: u32) -> u32 {
+        let previous = self.word.swap(value, Ordering::AcqRel);
+        self.wake_all();
+        previous
+    }
+
+    pub fn fetch_add(&self, value: u32) -> u32 {
+        let previous = self.word.fetch_add(value, Ordering::AcqRel);
+        self.wake_all();
+        previous
+    }
+
+    pub fn fetch_sub(&self, value: u32) -> u32 {
+        let previous = self.word.fetch_sub(value, Ordering::AcqRel);
+        self.wake_all();
+        previous
+    }
+
+    /// Stores `new` if the value is `current`, waking the waiting tasks on success
+    pub fn compare_exchange(&self, current: u32, new: u32) -> Result<u32, u32> {
+        let res = self
+            .word
+            .compare_exchange(current, new, Ordering::AcqRel, Ordering::Acquire);
+
+        if res.is_ok() {
+            self.wake_all();
+        }
+
+        res
+    }
+
+    /// Wakes one waiting task, returns the number of woken tasks
+    pub fn wake_one(&self) -> usize {
+        self.wake(1)
+    }
+
+    /// Wakes all the waiting tasks, returns the number of woken tasks
+    pub fn wake_all(&self) -> usize {
+        self.wake(i32::MAX)
+    }
+
+    /// Waits until the value is different from `current`, returning the new value
+    pub async fn wait(&self, current: u32) -> u32 {
+        self.wait_until(|value| value != current).await
+    }
+
+    /// Waits until the value matches the predicate, returning it
+    pub async fn wait_until(&self, mut f: impl FnMut(u32) -> bool) -> u32 {
+        loop {
+            let value = self.load();
+            if f(value) {
+                return value;
+            }
+
+            self.park(value).await;
+        }
+    }
+
+    /// Waits for a wake while the value is still `current`, may return spuriously
+    async fn park(&self, current: u32) {
+        if !crate::features().futex() {
+            crate::time::sleep(FALLBACK_INTERVAL).await;
+            return;
+        }
+
+        // fails with `EAGAIN` if the value already changed, which is checked by the caller
+        let _ = op::FutexWait::new(self.word.clone(), current)
+            .run_on(GlobalReactor)
+            .await;
+    }
+
+    fn wake(&self, count: i32) -> usize {
+        let woken = unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                self.word.as_ptr(),
+                libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                count,
+            )
+        };
+
+        woken.max(0) as usize
+    }
+}
+
+/// Counting semaphore that can be shared by tasks on different threads.
+///
+/// Unlike [Semaphore](crate::sync::Semaphore), permits are not handed out in FIFO order,
+/// each release wakes all waiting tasks and the first ones to run take the permits.
+#[derive(Debug, Default)]
+pub struct AtomicSemaphore {
+    permits: AtomicWaitCell,
+}
+
+impl AtomicSemaphore {
+    pub fn new(permits: u32) -> Self {
+        Self {
+            permits: AtomicWaitCell::new(permits),
+        }
+    }
+
+    pub fn available_permits(&self) -> u32 {
+        self.permits.load()
+    }
+
+    pub fn add_permits(&self, permits: u32) {
+        self.permits.fetch_add(permits);
+    }
+
+    pub async fn acquire(&self) -> AtomicSemaphorePermit<'_> {
+        self.acquire_many(1).await
+    }
+
+    pub async fn acquire_many(&self, permits: u32) -> AtomicSemaphorePermit<'_> {
+        loop {
+            if let Some(permit) = self.try_acquire_many(permits) {
+                return permit;
+            }
+
+            self.permits
+                .wait_until(|available| available >= permits)
+                .await;
+        }
+    }
+
+    pub fn try_acquire(&self) -> Option<AtomicSemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    pub fn try_acquire_many(&self, permits: u32) -> Option<AtomicSemaphorePermit<'_>> {
+        // taking permits never makes progress possible for others, so nobody is woken
+        self.permits
+            .word
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |available| {
+                available.checked_sub(permits)
+            })
+            .ok()
+            .map(|_| AtomicSemaphorePermit {
+                semaphore: self,
+                permits,
+            })
+    }
+}
+
+/// Permits acquired from an [AtomicSemaphore], released when dropped
+#[must_use]
+pub struct AtomicSemaphorePermit<'a> {
+    semaphore: &'a AtomicSemaphore,
+    permits: u32,
+}
+
+impl AtomicSemaphorePermit<'_> {
+    pub fn num_permits(&self) -> u32 {
+        self.permits
+    }
+
+    /// Drops the permit without releasing it back to the semaphore
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for AtomicSemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::sync::waiter::{WaitList, Waiter};
+
+struct BarrierState {
+    arrived: usize,
+    waiters: WaitList<()>,
+}
+
+/// Lets a fixed number of local tasks wait for each other.
+///
+/// The barrier is reusable, once all tasks arrive it starts counting again.
+pub struct Barrier {
+    count: usize,
+    state: RefCell<BarrierState>,
+}
+
+impl Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("count", &self.count)
+            .field("arrived", &self.state.borrow().arrived)
+            .finish()
+    }
+}
+
+impl Barrier {
+    /// Creates a barrier for `count` tasks, a count of zero behaves like one
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: count.max(1),
+            state: RefCell::new(BarrierState {
+                arrived: 0,
+                waiters: WaitList::default(),
+            }),
+        }
+    }
+
+    /// Waits until all the tasks reach the barrier.
+    /// Dropping the returned future before it completes withdraws the task.
+    pub fn wait(&self) -> BarrierWait<'_> {
+        BarrierWait {
+            barrier: self,
+            waiter: None,
+        }
+    }
+}
+
+/// Future returned by [Barrier::wait]
+pub struct BarrierWait<'a> {
+    barrier: &'a Barrier,
+    waiter: Option<Rc<Waiter>>,
+}
+
+impl Future for BarrierWait<'_> {
+    type Output = BarrierWaitResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        match this.waiter.as_ref() {
+            None => {
+                let mut state = this.barrier.state.borrow_mut();
+                state.arrived += 1;
+
+                if state.arrived == this.barrier.count {
+                    state.arrived = 0;
+                    state.waiters.wake_all();
+                    return Poll::Ready(BarrierWaitResult { leader: true });
+                }
+
+                let waiter = state.waiters.push(());
+                waiter.register(cx.waker());
+                this.waiter = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.is_woken() => {
+                this.waiter = None;
+                Poll::Ready(BarrierWaitResult { leader: false })
+            }
+            Some(waiter) => {
+                waiter.register(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for BarrierWait<'_> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+
+        let mut state = self.barrier.state.borrow_mut();
+        if state.waiters.remove(&waiter) {
+            state.arrived -= 1;
+        }
+    }
+}
+
+/// Returned by [Barrier::wait], exactly one task of each round is the leader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    leader: bool,
+}
+
+impl BarrierWaitResult {
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+}
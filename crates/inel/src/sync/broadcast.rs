@@ -0,0 +1,249 @@
+use std::{
+    cell::OnceCell,
+    collections::VecDeque,
+    fmt::{self, Display},
+};
+
+use crate::sync::{
+    flavor::{Flavor, Local, Shared, Waiters},
+    SendError,
+};
+
+struct State<T, F: Flavor> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+    // position of the first value in the buffer
+    head: u64,
+    senders: usize,
+    receivers: usize,
+    waiting: Waiters<F>,
+}
+
+impl<T, F: Flavor> State<T, F> {
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+}
+
+/// Creates a channel where every receiver gets each value sent after it subscribed.
+/// Only the last `capacity` values are kept, slower receivers skip the older ones.
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "channel capacity must be positive");
+    new(capacity)
+}
+
+/// Channels whose handles can be sent to tasks running on other threads
+pub mod shared {
+    use super::*;
+
+    /// Creates a broadcast channel, see [channel](super::channel)
+    pub fn channel<T: Clone + Send>(capacity: usize) -> (Sender<T, Shared>, Receiver<T, Shared>) {
+        assert!(capacity > 0, "channel capacity must be positive");
+        new(capacity)
+    }
+}
+
+fn new<T, F: Flavor>(capacity: usize) -> (Sender<T, F>, Receiver<T, F>) {
+    let state = F::cell(State {
+        buffer: VecDeque::with_capacity(capacity),
+        capacity,
+        head: 0,
+        senders: 1,
+        receivers: 1,
+        waiting: Waiters::default(),
+    });
+
+    let sender = Sender {
+        state: F::clone_cell(&state),
+    };
+
+    let receiver = Receiver {
+        state,
+        next: 0,
+        signal: OnceCell::new(),
+    };
+
+    (sender, receiver)
+}
+
+/// Returned by [Receiver::recv]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvError {
+    /// All senders are gone and every value was received
+    Closed,
+    /// The receiver fell behind and skipped this many values
+    Lagged(u64),
+}
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => f.write_str("channel closed"),
+            Self::Lagged(skipped) => write!(f, "receiver lagged by {skipped} values"),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Returned by [Receiver::try_recv]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Closed,
+    Lagged(u64),
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Closed => f.write_str("channel closed"),
+            Self::Lagged(skipped) => write!(f, "receiver lagged by {skipped} values"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+pub struct Sender<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+}
+
+impl<T, F: Flavor> Sender<T, F> {
+    /// Sends a value to all current receivers, returning how many there are.
+    /// Fails if there are no receivers.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let (receivers, waiting) = F::with(&self.state, |state| {
+            if state.receivers == 0 {
+                return Err(SendError(value));
+            }
+
+            state.buffer.push_back(value);
+            if state.buffer.len() > state.capacity {
+                state.buffer.pop_front();
+                state.head += 1;
+            }
+
+            Ok((state.receivers, state.waiting.take()))
+        })?;
+
+        waiting.notify();
+        Ok(receivers)
+    }
+
+    /// Creates a receiver that gets the values sent from now on
+    pub fn subscribe(&self) -> Receiver<T, F> {
+        let next = F::with(&self.state, |state| {
+            state.receivers += 1;
+            state.tail()
+        });
+
+        Receiver {
+            state: F::clone_cell(&self.state),
+            next,
+            signal: OnceCell::new(),
+        }
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        F::with(&self.state, |state| state.receivers)
+    }
+}
+
+impl<T, F: Flavor> Clone for Sender<T, F> {
+    fn clone(&self) -> Self {
+        F::with(&self.state, |state| state.senders += 1);
+
+        Self {
+            state: F::clone_cell(&self.state),
+        }
+    }
+}
+
+impl<T, F: Flavor> Drop for Sender<T, F> {
+    fn drop(&mut self) {
+        let waiting = F::with(&self.state, |state| {
+            state.senders -= 1;
+            if state.senders == 0 {
+                state.waiting.take()
+            } else {
+                Waiters::default()
+            }
+        });
+
+        waiting.notify();
+    }
+}
+
+pub struct Receiver<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+    next: u64,
+    signal: OnceCell<F::Signal>,
+}
+
+impl<T: Clone, F: Flavor> Receiver<T, F> {
+    /// Receives the next value, or reports how many values were skipped if it fell behind
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        let signal = self.signal.get_or_init(F::signal).clone();
+
+        loop {
+            match self.take(Some(&signal)) {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Closed) => return Err(RecvError::Closed),
+                Err(TryRecvError::Lagged(skipped)) => return Err(RecvError::Lagged(skipped)),
+                Err(TryRecvError::Empty) => F::wait(&signal).await,
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.take(None)
+    }
+
+    fn take(&mut self, signal: Option<&F::Signal>) -> Result<T, TryRecvError> {
+        F::with(&self.state, |state| {
+            if self.next < state.head {
+                let skipped = state.head - self.next;
+                self.next = state.head;
+                return Err(TryRecvError::Lagged(skipped));
+            }
+
+            if let Some(value) = state.buffer.get((self.next - state.head) as usize) {
+                self.next += 1;
+                return Ok(value.clone());
+            }
+
+            if state.senders == 0 {
+                return Err(TryRecvError::Closed);
+            }
+
+            if let Some(signal) = signal {
+                state.waiting.register(signal);
+            }
+            Err(TryRecvError::Empty)
+        })
+    }
+}
+
+impl<T, F: Flavor> Clone for Receiver<T, F> {
+    /// Creates a receiver at the same position as this one
+    fn clone(&self) -> Self {
+        F::with(&self.state, |state| state.receivers += 1);
+
+        Self {
+            state: F::clone_cell(&self.state),
+            next: self.next,
+            signal: OnceCell::new(),
+        }
+    }
+}
+
+impl<T, F: Flavor> Drop for Receiver<T, F> {
+    fn drop(&mut self) {
+        F::with(&self.state, |state| state.receivers -= 1);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    rc::Rc,
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+};
+
+use inel_reactor::op::{self, OpExt};
+use tracing::warn;
+
+use crate::GlobalReactor;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Selects how the state of a channel is shared between its handles, see [Local] and [Shared]
+pub trait Flavor: private::Sealed + Sized + 'static {
+    #[doc(hidden)]
+    type Cell<S>;
+    #[doc(hidden)]
+    type Signal: Clone;
+
+    #[doc(hidden)]
+    fn cell<S>(state: S) -> Self::Cell<S>;
+    #[doc(hidden)]
+    fn clone_cell<S>(cell: &Self::Cell<S>) -> Self::Cell<S>;
+    #[doc(hidden)]
+    fn with<S, R>(cell: &Self::Cell<S>, f: impl FnOnce(&mut S) -> R) -> R;
+
+    #[doc(hidden)]
+    fn signal() -> Self::Signal;
+    #[doc(hidden)]
+    fn same(a: &Self::Signal, b: &Self::Signal) -> bool;
+    #[doc(hidden)]
+    fn notify(signal: &Self::Signal);
+    #[doc(hidden)]
+    fn wait(signal: &Self::Signal) -> impl Future<Output = ()>;
+}
+
+/// Channel handles that stay on the current thread, using `Rc` and plain wakers
+#[derive(Debug)]
+pub enum Local {}
+
+/// Channel handles that can be sent to other threads.
+///
+/// A waiting task is woken through an eventfd read submitted to its own ring,
+/// so the thread notifying it never touches a foreign waker.
+#[derive(Debug)]
+pub enum Shared {}
+
+impl private::Sealed for Local {}
+impl private::Sealed for Shared {}
+
+#[doc(hidden)]
+#[derive(Default)]
+pub struct LocalSignal {
+    notified: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Flavor for Local {
+    type Cell<S> = Rc<RefCell<S>>;
+    type Signal = Rc<LocalSignal>;
+
+    fn cell<S>(state: S) -> Self::Cell<S> {
+        Rc::new(RefCell::new(state))
+    }
+
+    fn clone_cell<S>(cell: &Self::Cell<S>) -> Self::Cell<S> {
+        Rc::clone(cell)
+    }
+
+    fn with<S, R>(cell: &Self::Cell<S>, f: impl FnOnce(&mut S) -> R) -> R {
+        f(&mut cell.borrow_mut())
+    }
+
+    fn signal() -> Self::Signal {
+        Rc::new(LocalSignal::default())
+    }
+
+    fn same(a: &Self::Signal, b: &Self::Signal) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+
+    fn notify(signal: &Self::Signal) {
+        signal.notified.set(true);
+        if let Some(waker) = signal.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+
+    fn wait(signal: &Self::Signal) -> impl Future<Output = ()> {
+        poll_fn(|cx| {
+            if signal.notified.replace(false) {
+                return Poll::Ready(());
+            }
+
+            *signal.waker.borrow_mut() = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct EventSignal {
+    fd: OwnedFd,
+}
+
+impl EventSignal {
+    fn new() -> Self {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            panic!(
+                "Failed to create eventfd: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+
+        Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        }
+    }
+}
+
+impl Flavor for Shared {
+    type Cell<S> = Arc<Mutex<S>>;
+    type Signal = Arc<EventSignal>;
+
+    fn cell<S>(state: S) -> Self::Cell<S> {
+        Arc::new(Mutex::new(state))
+    }
+
+    fn clone_cell<S>(cell: &Self::Cell<S>) -> Self::Cell<S> {
+        Arc::clone(cell)
+    }
+
+    fn with<S, R>(cell: &Self::Cell<S>, f: impl FnOnce(&mut S) -> R) -> R {
+        f(&mut cell.lock().unwrap_or_else(|poison| poison.into_inner()))
+    }
+
+    fn signal() -> Self::Signal {
+        Arc::new(EventSignal::new())
+    }
+
+    fn same(a: &Self::Signal, b: &Self::Signal) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    fn notify(signal: &Self::Signal) {
+        unsafe { libc::eventfd_write(signal.fd.as_raw_fd(), 1) };
+    }
+
+    fn wait(signal: &Self::Signal) -> impl Future<Output = ()> {
+        let fd = signal.fd.as_raw_fd();
+
+        // the signal outlives the read, as it is owned by the waiting handle
+        async move {
+            let (_, res) = op::Read::new(&fd, Box::new([0; 8]))
+                .run_on(GlobalReactor)
+                .await;
+
+            if let Err(err) = res {
+                warn!(?err, "Failed to wait on eventfd");
+            }
+        }
+    }
+}
+
+/// Signals of the handles waiting on a channel
+pub(crate) struct Waiters<F: Flavor> {
+    signals: Vec<F::Signal>,
+}
+
+impl<F: Flavor> Default for Waiters<F> {
+    fn default() -> Self {
+        Self {
+            signals: Vec::new(),
+        }
+    }
+}
+
+impl<F: Flavor> Waiters<F> {
+    pub(crate) fn register(&mut self, signal: &F::Signal) {
+        if !self.signals.iter().any(|other| F::same(other, signal)) {
+            self.signals.push(signal.clone());
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Self {
+        std::mem::take(self)
+    }
+
+    /// Notifies every waiting handle, each of them checks the channel state again when woken
+    pub(crate) fn notify(self) {
+        self.signals.iter().for_each(F::notify);
+    }
+}
@@ -0,0 +1,23 @@
+mod atomic;
+mod barrier;
+mod error;
+mod flavor;
+mod mutex;
+mod notify;
+mod rwlock;
+mod semaphore;
+mod waiter;
+
+pub mod broadcast;
+pub mod mpsc;
+pub mod oneshot;
+pub mod watch;
+
+pub use atomic::{AtomicSemaphore, AtomicSemaphorePermit, AtomicWaitCell};
+pub use barrier::{Barrier, BarrierWait, BarrierWaitResult};
+pub use error::{RecvError, SendError, TryRecvError, TrySendError};
+pub use flavor::{Flavor, Local, Shared};
+pub use mutex::{Mutex, MutexGuard};
+pub use notify::{Notified, Notify};
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use semaphore::{Acquire, OwnedSemaphorePermit, Semaphore, SemaphorePermit};
@@ -0,0 +1,218 @@
+use std::{cell::OnceCell, collections::VecDeque};
+
+use crate::sync::{
+    flavor::{Flavor, Local, Shared, Waiters},
+    SendError, TryRecvError, TrySendError,
+};
+
+struct State<T, F: Flavor> {
+    queue: VecDeque<T>,
+    capacity: Option<usize>,
+    senders: usize,
+    closed: bool,
+    receiver: Waiters<F>,
+    waiting: Waiters<F>,
+}
+
+/// Creates a bounded channel, sending waits while `capacity` values are queued
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "channel capacity must be positive");
+    new(Some(capacity))
+}
+
+/// Creates an unbounded channel, sending never waits
+pub fn unbounded_channel<T>() -> (Sender<T>, Receiver<T>) {
+    new(None)
+}
+
+/// Channels whose handles can be sent to tasks running on other threads
+pub mod shared {
+    use super::*;
+
+    /// Creates a bounded channel, see [channel](super::channel)
+    pub fn channel<T: Send>(capacity: usize) -> (Sender<T, Shared>, Receiver<T, Shared>) {
+        assert!(capacity > 0, "channel capacity must be positive");
+        new(Some(capacity))
+    }
+
+    /// Creates an unbounded channel, see [unbounded_channel](super::unbounded_channel)
+    pub fn unbounded_channel<T: Send>() -> (Sender<T, Shared>, Receiver<T, Shared>) {
+        new(None)
+    }
+}
+
+fn new<T, F: Flavor>(capacity: Option<usize>) -> (Sender<T, F>, Receiver<T, F>) {
+    let state = F::cell(State {
+        queue: VecDeque::new(),
+        capacity,
+        senders: 1,
+        closed: false,
+        receiver: Waiters::default(),
+        waiting: Waiters::default(),
+    });
+
+    let sender = Sender {
+        state: F::clone_cell(&state),
+        signal: OnceCell::new(),
+    };
+
+    let receiver = Receiver {
+        state,
+        signal: OnceCell::new(),
+    };
+
+    (sender, receiver)
+}
+
+pub struct Sender<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+    signal: OnceCell<F::Signal>,
+}
+
+impl<T, F: Flavor> Sender<T, F> {
+    /// Sends a value, waiting for capacity if the channel is bounded and full.
+    /// Fails if the receiver was dropped or closed.
+    pub async fn send(&self, mut value: T) -> Result<(), SendError<T>> {
+        let signal = self.signal.get_or_init(F::signal);
+
+        loop {
+            match self.push(value, Some(signal)) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(value)) => return Err(SendError(value)),
+                Err(TrySendError::Full(rejected)) => value = rejected,
+            }
+
+            F::wait(signal).await;
+        }
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.push(value, None)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        F::with(&self.state, |state| state.closed)
+    }
+
+    /// Queues the value, or registers the signal to be notified when there is capacity
+    fn push(&self, value: T, signal: Option<&F::Signal>) -> Result<(), TrySendError<T>> {
+        let receiver = F::with(&self.state, |state| {
+            if state.closed {
+                return Err(TrySendError::Closed(value));
+            }
+
+            if state.capacity.is_some_and(|cap| state.queue.len() >= cap) {
+                if let Some(signal) = signal {
+                    state.waiting.register(signal);
+                }
+                return Err(TrySendError::Full(value));
+            }
+
+            state.queue.push_back(value);
+            Ok(state.receiver.take())
+        })?;
+
+        receiver.notify();
+        Ok(())
+    }
+}
+
+impl<T, F: Flavor> Clone for Sender<T, F> {
+    fn clone(&self) -> Self {
+        F::with(&self.state, |state| state.senders += 1);
+
+        Self {
+            state: F::clone_cell(&self.state),
+            signal: OnceCell::new(),
+        }
+    }
+}
+
+impl<T, F: Flavor> Drop for Sender<T, F> {
+    fn drop(&mut self) {
+        let receiver = F::with(&self.state, |state| {
+            state.senders -= 1;
+            if state.senders == 0 {
+                state.receiver.take()
+            } else {
+                Waiters::default()
+            }
+        });
+
+        receiver.notify();
+    }
+}
+
+pub struct Receiver<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+    signal: OnceCell<F::Signal>,
+}
+
+impl<T, F: Flavor> Receiver<T, F> {
+    /// Receives the next value, returns `None` once the channel is empty and all senders are gone
+    pub async fn recv(&mut self) -> Option<T> {
+        let signal = self.signal.get_or_init(F::signal);
+
+        loop {
+            match self.pop(Some(signal)) {
+                Ok(value) => return Some(value),
+                Err(TryRecvError::Closed) => return None,
+                Err(TryRecvError::Empty) => F::wait(signal).await,
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.pop(None)
+    }
+
+    /// Stops accepting values, the ones already queued can still be received
+    pub fn close(&mut self) {
+        let waiting = F::with(&self.state, |state| {
+            state.closed = true;
+            state.waiting.take()
+        });
+
+        waiting.notify();
+    }
+
+    pub fn len(&self) -> usize {
+        F::with(&self.state, |state| state.queue.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes the next value, or registers the signal to be notified when one is sent
+    fn pop(&self, signal: Option<&F::Signal>) -> Result<T, TryRecvError> {
+        let (res, waiting) = F::with(&self.state, |state| match state.queue.pop_front() {
+            Some(value) => (Ok(value), state.waiting.take()),
+            None if state.senders == 0 || state.closed => {
+                (Err(TryRecvError::Closed), Waiters::default())
+            }
+            None => {
+                if let Some(signal) = signal {
+                    state.receiver.register(signal);
+                }
+                (Err(TryRecvError::Empty), Waiters::default())
+            }
+        });
+
+        waiting.notify();
+        res
+    }
+}
+
+impl<T, F: Flavor> Drop for Receiver<T, F> {
+    fn drop(&mut self) {
+        self.close();
+
+        // values are dropped outside of the lock, in case they own a sender
+        let queue = F::with(&self.state, |state| std::mem::take(&mut state.queue));
+        drop(queue);
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::sync::waiter::{WaitList, Waiter};
+
+/// Notifies local tasks waiting on it.
+///
+/// A [Notify::notify_one] with no waiting tasks is stored as a single permit,
+/// which completes the next call to [Notify::notified] immediately.
+#[derive(Default)]
+pub struct Notify {
+    permit: Cell<bool>,
+    // the flag is set for waiters woken by `notify_one`
+    waiters: RefCell<WaitList<Rc<Cell<bool>>>>,
+}
+
+impl Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("permit", &self.permit.get())
+            .field("waiters", &self.waiters.borrow().len())
+            .finish()
+    }
+}
+
+impl Notify {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wakes the first waiting task, or stores a permit if there is none
+    pub fn notify_one(&self) {
+        match self.waiters.borrow_mut().wake_one() {
+            Some(single) => single.set(true),
+            None => self.permit.set(true),
+        }
+    }
+
+    /// Wakes all the waiting tasks, without storing a permit
+    pub fn notify_waiters(&self) {
+        self.waiters.borrow_mut().wake_all();
+    }
+
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            waiter: None,
+            single: Rc::new(Cell::new(false)),
+        }
+    }
+}
+
+/// Future returned by [Notify::notified].
+///
+/// Dropping it after being woken by [Notify::notify_one] passes the notification on.
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    waiter: Option<Rc<Waiter>>,
+    single: Rc<Cell<bool>>,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let notify = this.notify;
+
+        match this.waiter.as_ref() {
+            None if notify.permit.replace(false) => Poll::Ready(()),
+            None => {
+                let waiter = notify.waiters.borrow_mut().push(this.single.clone());
+                waiter.register(cx.waker());
+                this.waiter = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.is_woken() => {
+                this.waiter = None;
+                Poll::Ready(())
+            }
+            Some(waiter) => {
+                waiter.register(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+
+        if !waiter.is_woken() {
+            self.notify.waiters.borrow_mut().remove(&waiter);
+        } else if self.single.get() {
+            self.notify.notify_one();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::cell::OnceCell;
+
+use crate::sync::{
+    flavor::{Flavor, Local, Shared, Waiters},
+    RecvError, TryRecvError,
+};
+
+struct State<T, F: Flavor> {
+    value: Option<T>,
+    complete: bool,
+    closed: bool,
+    receiver: Waiters<F>,
+}
+
+/// Creates a channel for sending a single value
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    new()
+}
+
+/// Channels whose handles can be sent to tasks running on other threads
+pub mod shared {
+    use super::*;
+
+    /// Creates a channel for sending a single value, see [channel](super::channel)
+    pub fn channel<T: Send>() -> (Sender<T, Shared>, Receiver<T, Shared>) {
+        new()
+    }
+}
+
+fn new<T, F: Flavor>() -> (Sender<T, F>, Receiver<T, F>) {
+    let state = F::cell(State {
+        value: None,
+        complete: false,
+        closed: false,
+        receiver: Waiters::default(),
+    });
+
+    let sender = Sender {
+        state: F::clone_cell(&state),
+    };
+
+    let receiver = Receiver {
+        state,
+        signal: OnceCell::new(),
+    };
+
+    (sender, receiver)
+}
+
+pub struct Sender<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+}
+
+impl<T, F: Flavor> Sender<T, F> {
+    /// Sends the value, giving it back if the receiver was dropped or closed
+    pub fn send(self, value: T) -> Result<(), T> {
+        F::with(&self.state, |state| {
+            if state.closed {
+                return Err(value);
+            }
+
+            state.value = Some(value);
+            Ok(())
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        F::with(&self.state, |state| state.closed)
+    }
+}
+
+impl<T, F: Flavor> Drop for Sender<T, F> {
+    fn drop(&mut self) {
+        let receiver = F::with(&self.state, |state| {
+            state.complete = true;
+            state.receiver.take()
+        });
+
+        receiver.notify();
+    }
+}
+
+pub struct Receiver<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+    signal: OnceCell<F::Signal>,
+}
+
+impl<T, F: Flavor> Receiver<T, F> {
+    /// Waits for the value, fails if the sender was dropped without sending one
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        let signal = self.signal.get_or_init(F::signal);
+
+        loop {
+            match self.take(Some(signal)) {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Closed) => return Err(RecvError),
+                Err(TryRecvError::Empty) => F::wait(signal).await,
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.take(None)
+    }
+
+    /// Prevents the sender from sending a value
+    pub fn close(&mut self) {
+        F::with(&self.state, |state| state.closed = true);
+    }
+
+    fn take(&self, signal: Option<&F::Signal>) -> Result<T, TryRecvError> {
+        F::with(&self.state, |state| match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.complete => Err(TryRecvError::Closed),
+            None => {
+                if let Some(signal) = signal {
+                    state.receiver.register(signal);
+                }
+                Err(TryRecvError::Empty)
+            }
+        })
+    }
+}
+
+impl<T, F: Flavor> Drop for Receiver<T, F> {
+    fn drop(&mut self) {
+        let value = F::with(&self.state, |state| {
+            state.closed = true;
+            state.value.take()
+        });
+
+        drop(value);
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    ops::{Deref, DerefMut},
+};
+
+use crate::sync::Semaphore;
+
+const MAX_READS: usize = u32::MAX as usize;
+
+/// Reader-writer lock for local tasks, which can be held across await points.
+///
+/// Readers and writers are served in FIFO order, so a waiting writer blocks new readers.
+pub struct RwLock<T: ?Sized> {
+    semaphore: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLock")
+            .field("readers", &(MAX_READS - self.semaphore.available_permits()))
+            .finish()
+    }
+}
+
+impl<T> RwLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(MAX_READS),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.semaphore.acquire().await.forget();
+        RwLockReadGuard { lock: self }
+    }
+
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.semaphore.acquire_many(MAX_READS).await.forget();
+        RwLockWriteGuard { lock: self }
+    }
+
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        self.semaphore.try_acquire().map(|permit| {
+            permit.forget();
+            RwLockReadGuard { lock: self }
+        })
+    }
+
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.semaphore.try_acquire_many(MAX_READS).map(|permit| {
+            permit.forget();
+            RwLockWriteGuard { lock: self }
+        })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+/// Shared access to the value of a [RwLock], released when dropped
+#[must_use]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(1);
+    }
+}
+
+/// Exclusive access to the value of a [RwLock], released when dropped
+#[must_use]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(MAX_READS);
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::sync::waiter::{WaitList, Waiter};
+
+/// Counting semaphore for local tasks.
+///
+/// Permits are handed out in FIFO order, so a large request is not starved by smaller ones.
+pub struct Semaphore {
+    permits: Cell<usize>,
+    waiters: RefCell<WaitList<usize>>,
+}
+
+impl Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.permits.get())
+            .finish()
+    }
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Self {
+        Self {
+            permits: Cell::new(permits),
+            waiters: RefCell::new(WaitList::default()),
+        }
+    }
+
+    pub fn available_permits(&self) -> usize {
+        self.permits.get()
+    }
+
+    /// Adds permits, waking waiters in order while their requests can be satisfied
+    pub fn add_permits(&self, permits: usize) {
+        self.permits.set(self.permits.get() + permits);
+        self.assign();
+    }
+
+    pub fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Waits for `permits` permits, which are released together when the returned permit is dropped
+    pub fn acquire_many(&self, permits: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            permits,
+            waiter: None,
+        }
+    }
+
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    pub fn try_acquire_many(&self, permits: usize) -> Option<SemaphorePermit<'_>> {
+        self.try_take(permits).then(|| SemaphorePermit {
+            semaphore: self,
+            permits,
+        })
+    }
+
+    /// Waits for a permit that is not bound to the lifetime of the semaphore
+    pub async fn acquire_owned(self: Rc<Self>) -> OwnedSemaphorePermit {
+        self.acquire_many_owned(1).await
+    }
+
+    pub async fn acquire_many_owned(self: Rc<Self>, permits: usize) -> OwnedSemaphorePermit {
+        self.acquire_many(permits).await.forget();
+        OwnedSemaphorePermit {
+            semaphore: self,
+            permits,
+        }
+    }
+
+    fn try_take(&self, permits: usize) -> bool {
+        if !self.waiters.borrow().is_empty() || self.permits.get() < permits {
+            return false;
+        }
+
+        self.permits.set(self.permits.get() - permits);
+        true
+    }
+
+    fn release(&self, permits: usize) {
+        self.add_permits(permits);
+    }
+
+    /// Hands out permits to the waiters at the front of the queue
+    fn assign(&self) {
+        let mut waiters = self.waiters.borrow_mut();
+        while let Some(&wanted) = waiters.front() {
+            if wanted > self.permits.get() {
+                break;
+            }
+
+            self.permits.set(self.permits.get() - wanted);
+            waiters.wake_one();
+        }
+    }
+}
+
+/// Future returned by [Semaphore::acquire], dropping it gives back any assigned permits
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+    waiter: Option<Rc<Waiter>>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let semaphore = this.semaphore;
+
+        match this.waiter.as_ref() {
+            None if semaphore.try_take(this.permits) => {}
+            None => {
+                let waiter = semaphore.waiters.borrow_mut().push(this.permits);
+                waiter.register(cx.waker());
+                this.waiter = Some(waiter);
+                return Poll::Pending;
+            }
+            Some(waiter) if waiter.is_woken() => {
+                this.waiter = None;
+            }
+            Some(waiter) => {
+                waiter.register(cx.waker());
+                return Poll::Pending;
+            }
+        }
+
+        Poll::Ready(SemaphorePermit {
+            semaphore,
+            permits: this.permits,
+        })
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+
+        if waiter.is_woken() {
+            self.semaphore.release(self.permits);
+        } else {
+            self.semaphore.waiters.borrow_mut().remove(&waiter);
+            // the removed waiter might have been blocking the ones behind it
+            self.semaphore.assign();
+        }
+    }
+}
+
+/// Permits acquired from a [Semaphore], released when dropped
+#[must_use]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Drops the permit without releasing it back to the semaphore
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.release(self.permits);
+        }
+    }
+}
+
+/// Permits acquired from a shared [Semaphore], released when dropped
+#[must_use]
+pub struct OwnedSemaphorePermit {
+    semaphore: Rc<Semaphore>,
+    permits: usize,
+}
+
+impl OwnedSemaphorePermit {
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.release(self.permits);
+        }
+    }
+}
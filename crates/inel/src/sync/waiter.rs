@@ -0,0 +1,84 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+    task::Waker,
+};
+
+/// A task waiting in a [WaitList], woken at most once
+#[derive(Default)]
+pub(crate) struct Waiter {
+    waker: RefCell<Option<Waker>>,
+    woken: Cell<bool>,
+}
+
+impl Waiter {
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut current = self.waker.borrow_mut();
+        match current.as_ref() {
+            Some(current) if current.will_wake(waker) => {}
+            _ => *current = Some(waker.clone()),
+        }
+    }
+
+    pub(crate) fn is_woken(&self) -> bool {
+        self.woken.get()
+    }
+
+    pub(crate) fn wake(&self) {
+        self.woken.set(true);
+        if let Some(waker) = self.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// FIFO queue of waiting tasks, each with an associated value
+pub(crate) struct WaitList<T> {
+    waiters: VecDeque<(T, Rc<Waiter>)>,
+}
+
+impl<T> Default for WaitList<T> {
+    fn default() -> Self {
+        Self {
+            waiters: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> WaitList<T> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.waiters.len()
+    }
+
+    pub(crate) fn push(&mut self, value: T) -> Rc<Waiter> {
+        let waiter = Rc::new(Waiter::default());
+        self.waiters.push_back((value, Rc::clone(&waiter)));
+        waiter
+    }
+
+    pub(crate) fn front(&self) -> Option<&T> {
+        self.waiters.front().map(|(value, _)| value)
+    }
+
+    /// Wakes the first waiter, returning its value
+    pub(crate) fn wake_one(&mut self) -> Option<T> {
+        let (value, waiter) = self.waiters.pop_front()?;
+        waiter.wake();
+        Some(value)
+    }
+
+    pub(crate) fn wake_all(&mut self) {
+        while self.wake_one().is_some() {}
+    }
+
+    /// Removes a waiter that was not woken, returns false if it was not found
+    pub(crate) fn remove(&mut self, waiter: &Rc<Waiter>) -> bool {
+        let position = self.waiters.iter().position(|(_, w)| Rc::ptr_eq(w, waiter));
+        position.and_then(|i| self.waiters.remove(i)).is_some()
+    }
+}
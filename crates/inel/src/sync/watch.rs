@@ -0,0 +1,204 @@
+use std::cell::OnceCell;
+
+use crate::sync::{
+    flavor::{Flavor, Local, Shared, Waiters},
+    RecvError, SendError,
+};
+
+struct State<T, F: Flavor> {
+    value: T,
+    version: u64,
+    closed: bool,
+    receivers: usize,
+    waiting: Waiters<F>,
+}
+
+/// Creates a channel that holds a single value, receivers are notified when it changes
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    new(init)
+}
+
+/// Channels whose handles can be sent to tasks running on other threads
+pub mod shared {
+    use super::*;
+
+    /// Creates a watch channel, see [channel](super::channel)
+    pub fn channel<T: Send>(init: T) -> (Sender<T, Shared>, Receiver<T, Shared>) {
+        new(init)
+    }
+}
+
+fn new<T, F: Flavor>(init: T) -> (Sender<T, F>, Receiver<T, F>) {
+    let state = F::cell(State {
+        value: init,
+        version: 0,
+        closed: false,
+        receivers: 1,
+        waiting: Waiters::default(),
+    });
+
+    let sender = Sender {
+        state: F::clone_cell(&state),
+    };
+
+    let receiver = Receiver {
+        state,
+        seen: 0,
+        signal: OnceCell::new(),
+    };
+
+    (sender, receiver)
+}
+
+pub struct Sender<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+}
+
+impl<T, F: Flavor> Sender<T, F> {
+    /// Replaces the value and notifies the receivers, fails if there are none
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.receiver_count() == 0 {
+            return Err(SendError(value));
+        }
+
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replaces the value even if there are no receivers, returning the previous one
+    pub fn send_replace(&self, value: T) -> T {
+        let mut value = Some(value);
+        let mut previous = None;
+        self.send_modify(|current| {
+            previous = Some(std::mem::replace(current, value.take().unwrap()))
+        });
+        previous.unwrap()
+    }
+
+    /// Modifies the value in place and notifies the receivers
+    pub fn send_modify(&self, f: impl FnOnce(&mut T)) {
+        let waiting = F::with(&self.state, |state| {
+            f(&mut state.value);
+            state.version += 1;
+            state.waiting.take()
+        });
+
+        waiting.notify();
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        F::with(&self.state, |state| f(&state.value))
+    }
+
+    /// Creates a receiver that has seen the current value
+    pub fn subscribe(&self) -> Receiver<T, F> {
+        let seen = F::with(&self.state, |state| {
+            state.receivers += 1;
+            state.version
+        });
+
+        Receiver {
+            state: F::clone_cell(&self.state),
+            seen,
+            signal: OnceCell::new(),
+        }
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        F::with(&self.state, |state| state.receivers)
+    }
+}
+
+impl<T, F: Flavor> Drop for Sender<T, F> {
+    fn drop(&mut self) {
+        let waiting = F::with(&self.state, |state| {
+            state.closed = true;
+            state.waiting.take()
+        });
+
+        waiting.notify();
+    }
+}
+
+pub struct Receiver<T, F: Flavor = Local> {
+    state: F::Cell<State<T, F>>,
+    seen: u64,
+    signal: OnceCell<F::Signal>,
+}
+
+impl<T, F: Flavor> Receiver<T, F> {
+    /// Waits until the value changes after it was last seen, fails if the sender was dropped
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let signal = self.signal.get_or_init(F::signal).clone();
+
+        loop {
+            let res = F::with(&self.state, |state| {
+                if state.version != self.seen {
+                    self.seen = state.version;
+                    return Some(Ok(()));
+                }
+
+                if state.closed {
+                    return Some(Err(RecvError));
+                }
+
+                state.waiting.register(&signal);
+                None
+            });
+
+            match res {
+                Some(res) => return res,
+                None => F::wait(&signal).await,
+            }
+        }
+    }
+
+    /// Returns true if the value changed since it was last seen, fails if the sender was dropped
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        F::with(&self.state, |state| match state.closed {
+            true => Err(RecvError),
+            false => Ok(state.version != self.seen),
+        })
+    }
+
+    /// Reads the value, without marking it as seen
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        F::with(&self.state, |state| f(&state.value))
+    }
+
+    /// Reads the value, marking it as seen
+    pub fn with_and_update<R>(&mut self, f: impl FnOnce(&T) -> R) -> R {
+        F::with(&self.state, |state| {
+            self.seen = state.version;
+            f(&state.value)
+        })
+    }
+}
+
+impl<T: Clone, F: Flavor> Receiver<T, F> {
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+
+    pub fn get_and_update(&mut self) -> T {
+        self.with_and_update(T::clone)
+    }
+}
+
+impl<T, F: Flavor> Clone for Receiver<T, F> {
+    fn clone(&self) -> Self {
+        F::with(&self.state, |state| state.receivers += 1);
+
+        Self {
+            state: F::clone_cell(&self.state),
+            seen: self.seen,
+            signal: OnceCell::new(),
+        }
+    }
+}
+
+impl<T, F: Flavor> Drop for Receiver<T, F> {
+    fn drop(&mut self) {
+        F::with(&self.state, |state| state.receivers -= 1);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashMap, future::Future};
+
+use futures::{future::LocalBoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+use inel_executor::{AbortHandle, JoinHandle};
+
+/// A set of spawned tasks, whose results are returned in the order they finish.
+///
+/// Dropping the set aborts the tasks that are still running.
+pub struct JoinSet<T> {
+    running: FuturesUnordered<LocalBoxFuture<'static, (u64, Option<T>)>>,
+    aborts: HashMap<u64, AbortHandle>,
+    next: u64,
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self {
+            running: FuturesUnordered::new(),
+            aborts: HashMap::new(),
+            next: 0,
+        }
+    }
+}
+
+impl<T: 'static> JoinSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn<F>(&mut self, future: F) -> AbortHandle
+    where
+        F: Future<Output = T> + 'static,
+    {
+        self.insert(crate::spawn(future))
+    }
+
+    /// Adds an already spawned task to the set
+    pub fn insert(&mut self, handle: JoinHandle<T>) -> AbortHandle {
+        let id = self.next;
+        self.next += 1;
+
+        let abort = handle.abort_handle();
+        self.aborts.insert(id, abort.clone());
+        self.running
+            .push(handle.join().map(move |res| (id, res)).boxed_local());
+
+        abort
+    }
+
+    pub fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    /// Waits for the next task to finish, returns `None` if the set is empty.
+    /// Aborted tasks are returned as `Some(None)`.
+    pub async fn join_next(&mut self) -> Option<Option<T>> {
+        let (id, res) = self.running.next().await?;
+        self.aborts.remove(&id);
+        Some(res)
+    }
+
+    /// Returns the result of a finished task, without waiting
+    pub fn try_join_next(&mut self) -> Option<Option<T>> {
+        self.join_next().now_or_never().flatten()
+    }
+
+    /// Waits for all the tasks, returning the results of the ones that were not aborted
+    pub async fn join_all(mut self) -> Vec<T> {
+        let mut results = Vec::with_capacity(self.len());
+        while let Some(res) = self.join_next().await {
+            results.extend(res);
+        }
+        results
+    }
+
+    /// Removes all the tasks from the set without aborting them
+    pub fn detach_all(&mut self) {
+        self.running.clear();
+        self.aborts.clear();
+    }
+
+    /// Aborts all the tasks, they are still returned by [JoinSet::join_next]
+    pub fn abort_all(&mut self) {
+        self.aborts.values().for_each(AbortHandle::abort);
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.aborts.values().for_each(AbortHandle::abort);
+    }
+}
@@ -0,0 +1,9 @@
+mod join_set;
+mod traced;
+mod yield_now;
+
+pub use inel_executor::{AbortHandle, AccessError, JoinHandle, LocalKey, Priority, Scope};
+pub use join_set::JoinSet;
+pub use yield_now::{yield_now, YieldNow};
+
+pub(crate) use traced::Traced;
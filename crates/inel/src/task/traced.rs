@@ -0,0 +1,80 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use pin_project_lite::pin_project;
+use tracing::{debug, trace, Span};
+
+use crate::REACTOR;
+
+pin_project! {
+    /// Runs a task inside its [Span], recording the number of polls,
+    /// the time spent polling and the number of submitted entries
+    pub(crate) struct Traced<F> {
+        #[pin]
+        inner: F,
+        span: Span,
+        polls: u64,
+        sqes: u64,
+        busy: Duration,
+    }
+}
+
+impl<F> Traced<F> {
+    pub(crate) fn new(inner: F, span: Span) -> Self {
+        Self {
+            inner,
+            span,
+            polls: 0,
+            sqes: 0,
+            busy: Duration::ZERO,
+        }
+    }
+}
+
+fn submitted() -> u64 {
+    REACTOR
+        .try_with(|react| {
+            let react = react.try_borrow().ok()?;
+            react.as_ref().map(|react| react.submitted())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or(0)
+}
+
+impl<F: Future> Future for Traced<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _entered = this.span.enter();
+
+        let before = submitted();
+        let start = Instant::now();
+
+        let res = this.inner.poll(cx);
+
+        let elapsed = start.elapsed();
+        let sqes = submitted().saturating_sub(before);
+
+        *this.polls += 1;
+        *this.sqes += sqes;
+        *this.busy += elapsed;
+
+        this.span.record("polls", *this.polls);
+        this.span.record("sqes", *this.sqes);
+        this.span.record("busy_us", this.busy.as_micros() as u64);
+
+        trace!(?elapsed, sqes, "Task poll");
+
+        if res.is_ready() {
+            debug!(polls = *this.polls, sqes = *this.sqes, busy = ?*this.busy, "Task finished");
+        }
+
+        res
+    }
+}
@@ -1,11 +1,15 @@
 use std::{
     future::Future,
-    pin::Pin,
+    io::{Error, ErrorKind, Result},
+    pin::{pin, Pin},
     task::{Context, Poll},
     time::Duration,
 };
 
-use futures::{future::FusedFuture, FutureExt};
+use futures::{
+    future::{self, Either, FusedFuture},
+    FutureExt,
+};
 use inel_reactor::{
     op::{self, OpExt},
     submission::Submission,
@@ -43,6 +47,15 @@ impl FusedFuture for Timeout {
     }
 }
 
+/// Runs the future until it completes or the duration elapses,
+/// in which case the future is dropped and an error of kind [ErrorKind::TimedOut] is returned
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output> {
+    match future::select(pin!(future), sleep(duration)).await {
+        Either::Left((res, _)) => Ok(res),
+        Either::Right(_) => Err(Error::new(ErrorKind::TimedOut, "future timed out")),
+    }
+}
+
 pub fn instant() -> Instant {
     Instant::default()
 }
@@ -390,8 +390,10 @@ mod axum {
     use super::*;
 
     use std::{
+        future::Future,
         pin::Pin,
         task::{Context, Poll},
+        time::Duration,
     };
 
     use ::axum::{routing::get, Router};
@@ -424,20 +426,223 @@ mod axum {
         run_server(Serve::builder().with_shared_buffers(group));
     }
 
-    pub fn run_server(options: Serve) {
-        const MESSAGE: &str = "Hello World!";
+    #[test]
+    fn graceful() {
+        setup_tracing();
+
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                inel::time::sleep(Duration::from_millis(100)).await;
+                "done"
+            }),
+        );
+        let port = inel::block_on(find_open_port());
+
+        let (send, recv) = futures::channel::oneshot::channel::<()>();
+
+        let server = inel::spawn(async move {
+            Serve::builder()
+                .with_graceful_shutdown(recv.map(|_| ()))
+                .serve(("127.0.0.1", port), app)
+                .await
+        });
+
+        inel::block_on(async move {
+            inel::time::sleep(Duration::from_millis(10)).await;
+
+            let stream = inel::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            let mut client =
+                inel::compat::hyper::HyperClient::handshake_http1(BufStream::new(stream))
+                    .await
+                    .unwrap();
+
+            let req = Request::builder()
+                .uri("/slow")
+                .body(::axum::body::Body::empty())
+                .unwrap();
+
+            let res = inel::spawn(async move { client.send_request(req).await });
+
+            inel::time::sleep(Duration::from_millis(10)).await;
+            send.send(()).unwrap();
+
+            let res = res.join().await.unwrap().unwrap();
+            let mut stream = FrameStream::new(res.into_body());
+            let data = stream.next().await.unwrap().unwrap().into_data().unwrap();
+            assert_eq!(data, "done".as_bytes());
+
+            assert!(server.join().await.unwrap().is_ok());
+
+            let res = inel::net::TcpStream::connect(("127.0.0.1", port)).await;
+            assert!(res.is_err());
+        });
 
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn graceful_stops_accepting() {
         setup_tracing();
 
-        let app = Router::new().route("/hello", get(|| async { MESSAGE }));
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                inel::time::sleep(Duration::from_millis(100)).await;
+                "done"
+            }),
+        );
         let port = inel::block_on(find_open_port());
 
+        let (send, recv) = futures::channel::oneshot::channel::<()>();
+
+        let server = inel::spawn(async move {
+            Serve::builder()
+                .with_graceful_shutdown(recv.map(|_| ()))
+                .serve(("127.0.0.1", port), app)
+                .await
+        });
+
+        inel::block_on(async move {
+            inel::time::sleep(Duration::from_millis(10)).await;
+
+            let stream = inel::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            let mut client =
+                inel::compat::hyper::HyperClient::handshake_http1(BufStream::new(stream))
+                    .await
+                    .unwrap();
+
+            let req = Request::builder()
+                .uri("/slow")
+                .body(::axum::body::Body::empty())
+                .unwrap();
+
+            let res = inel::spawn(async move { client.send_request(req).await });
+
+            inel::time::sleep(Duration::from_millis(10)).await;
+            send.send(()).unwrap();
+            inel::time::sleep(Duration::from_millis(10)).await;
+
+            // the listener is closed while the open connection is still draining
+            let refused = inel::net::TcpStream::connect(("127.0.0.1", port)).await;
+            assert!(refused.is_err());
+
+            assert!(res.join().await.unwrap().is_ok());
+            assert!(server.join().await.unwrap().is_ok());
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn graceful_timeout() {
+        setup_tracing();
+
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                inel::time::sleep(Duration::from_millis(500)).await;
+                "done"
+            }),
+        );
+        let port = inel::block_on(find_open_port());
+
+        let (send, recv) = futures::channel::oneshot::channel::<()>();
+
+        let server = inel::spawn(async move {
+            Serve::builder()
+                .with_graceful_shutdown(recv.map(|_| ()))
+                .with_shutdown_timeout(Duration::from_millis(50))
+                .serve(("127.0.0.1", port), app)
+                .await
+        });
+
+        inel::block_on(async move {
+            inel::time::sleep(Duration::from_millis(10)).await;
+
+            let stream = inel::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            let mut client =
+                inel::compat::hyper::HyperClient::handshake_http1(BufStream::new(stream))
+                    .await
+                    .unwrap();
+
+            let req = Request::builder()
+                .uri("/slow")
+                .body(::axum::body::Body::empty())
+                .unwrap();
+
+            let res = inel::spawn(async move { client.send_request(req).await });
+
+            inel::time::sleep(Duration::from_millis(10)).await;
+            send.send(()).unwrap();
+
+            let start = std::time::Instant::now();
+            assert!(server.join().await.unwrap().is_ok());
+            assert!(start.elapsed() < Duration::from_millis(400));
+
+            // the connection is aborted, so the request never gets a response
+            assert!(res.join().await.unwrap().is_err());
+            assert!(start.elapsed() < Duration::from_millis(400));
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn sharded() {
+        setup_tracing();
+
+        let port = inel::block_on(find_open_port());
+        let shards = inel::block_on(inel::net::TcpListener::bind_sharded(
+            ("127.0.0.1", port),
+            2,
+            inel::net::Steering::Hash,
+        ))
+        .unwrap();
+
+        run_clients(port, move |app| async move {
+            let servers = shards
+                .into_iter()
+                .map(|incoming| Serve::builder().serve_incoming(incoming, app.clone()));
+
+            futures::future::join_all(servers).await;
+        });
+    }
+
+    pub fn run_server(options: Serve) {
+        setup_tracing();
+
+        let port = inel::block_on(find_open_port());
+
+        run_clients(port, move |app| async move {
+            options.serve(("127.0.0.1", port), app).await.unwrap();
+        });
+    }
+
+    fn run_clients<F, Fut>(port: u16, serve: F)
+    where
+        F: FnOnce(Router) -> Fut + 'static,
+        Fut: Future<Output = ()>,
+    {
+        const MESSAGE: &str = "Hello World!";
+
+        let app = Router::new().route("/hello", get(|| async { MESSAGE }));
+
         let (send, mut recv) = futures::channel::oneshot::channel();
 
         inel::spawn(async move {
             info!("Starting server");
             futures::select! {
-                res = options.serve(("127.0.0.1", port), app).fuse() => res.unwrap(),
+                _ = serve(app).fuse() => (),
                 res = recv => res.unwrap()
             };
             info!("Stopped server");
@@ -99,6 +99,41 @@ fn lines() {
     std::fs::remove_file(&name).unwrap();
 }
 
+#[test]
+fn budget() {
+    setup_tracing();
+
+    let name = temp_file();
+    let name_clone = name.clone();
+
+    std::fs::write(&name, [b'a'; 4096]).unwrap();
+
+    let interrupted = inel::block_on(async move {
+        let file = inel::fs::File::open(name_clone).await.unwrap();
+        let mut reader = inel::io::BufReader::new(file);
+        reader.fill_buf().await.unwrap();
+
+        let other = std::rc::Rc::new(std::cell::Cell::new(false));
+        let other_clone = other.clone();
+        inel::spawn(async move { other_clone.set(true) });
+
+        // every read is served from the buffer, but the other task still gets to run
+        let mut byte = [0; 1];
+        for i in 0..4096 {
+            reader.read_exact(&mut byte).await.unwrap();
+            if other.get() {
+                return Some(i);
+            }
+        }
+
+        None
+    });
+
+    assert!(interrupted.is_some_and(|i| i < 4096));
+
+    std::fs::remove_file(&name).unwrap();
+}
+
 #[test]
 fn error() {
     setup_tracing();
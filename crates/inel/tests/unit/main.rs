@@ -1,6 +1,9 @@
 mod fs;
 mod io;
 mod net;
+mod runtime;
+mod sync;
+mod task;
 
 #[cfg(feature = "compat")]
 mod compat;
@@ -97,6 +100,195 @@ fn init() {
     assert!(inel::is_done());
 }
 
+#[test]
+fn try_init() {
+    setup_tracing();
+
+    let err = inel::try_init(RingOptions::default().submissions(0)).unwrap_err();
+    assert!(err.to_string().contains("failed to create io_uring"));
+
+    inel::try_init(RingOptions::default().submissions(64)).unwrap();
+
+    let buffer = inel::buffer::Fixed::new(64).unwrap();
+    assert!(inel::try_init(RingOptions::default()).is_err());
+    drop(buffer);
+
+    inel::block_on(async {
+        inel::time::instant().await;
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn submit_policy() {
+    setup_tracing();
+
+    inel::init(RingOptions::default().submit_policy(inel::SubmitPolicy::Tick));
+
+    let res = inel::block_on(async {
+        let sleeps = (0..16).map(|_| inel::time::sleep(std::time::Duration::from_millis(1)));
+        futures::future::join_all(sleeps).await;
+
+        inel::spawn(async { 42 }).join().await
+    });
+
+    assert_eq!(res, Some(42));
+    assert!(inel::is_done());
+}
+
+#[test]
+fn wait_strategy() {
+    setup_tracing();
+
+    let timeout = std::time::Duration::from_millis(5);
+    inel::init(RingOptions::default().wait_strategy(inel::WaitStrategy::Timeout(timeout)));
+
+    inel::block_on(async {
+        inel::time::sleep(std::time::Duration::from_millis(50)).await;
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn features() {
+    setup_tracing();
+
+    let features = inel::features();
+    assert!(features.supports(0));
+    assert!(!features.supports(u8::MAX));
+}
+
+#[test]
+fn metrics() {
+    setup_tracing();
+
+    let before = inel::metrics();
+
+    let buffer = inel::buffer::Fixed::new(4096).unwrap();
+    assert_eq!(
+        inel::metrics().ring.fixed_buffers.used,
+        before.ring.fixed_buffers.used + 1
+    );
+    drop(buffer);
+
+    inel::block_on(async {
+        for _ in 0..4 {
+            inel::spawn(async {
+                inel::time::instant().await;
+            });
+        }
+    });
+
+    let after = inel::metrics();
+    assert_eq!(after.executor.spawned, before.executor.spawned + 5);
+    assert!(after.executor.polls >= before.executor.polls + 9);
+    assert_eq!(after.executor.tasks, 0);
+    assert_eq!(after.ring.submitted, before.ring.submitted + 4);
+    assert_eq!(after.ring.completed, before.ring.completed + 4);
+    assert_eq!(after.ring.active, 0);
+    assert_eq!(after.ring.fixed_buffers, before.ring.fixed_buffers);
+}
+
+#[test]
+fn spawn_named() {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    #[derive(Clone, Default)]
+    struct Recorded(Arc<Mutex<Vec<(String, u64)>>>);
+
+    impl Visit for Recorded {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), value));
+        }
+
+        fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for Recorded {
+        fn on_record(
+            &self,
+            _: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _: Context<'_, S>,
+        ) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    let recorded = Recorded::default();
+    let subscriber = tracing_subscriber::registry().with(recorded.clone());
+
+    let res = tracing::subscriber::with_default(subscriber, || {
+        inel::block_on(async {
+            let handle = inel::spawn_named("sleeper", async {
+                inel::time::sleep(std::time::Duration::from_millis(1)).await;
+                inel::time::instant().await;
+                7
+            });
+
+            handle.join().await
+        })
+    });
+
+    assert_eq!(res, Some(7));
+
+    let recorded = recorded.0.lock().unwrap();
+    let last = |name: &str| {
+        recorded
+            .iter()
+            .rev()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| *value)
+    };
+
+    assert_eq!(last("polls"), Some(3));
+    assert_eq!(last("sqes"), Some(2));
+    assert!(last("busy_us").is_some());
+}
+
+#[test]
+fn spawn_instrumented() {
+    setup_tracing();
+
+    let res = inel::block_on(async {
+        let span = tracing::info_span!("custom");
+        inel::spawn_instrumented(async { inel::time::instant().await }, span)
+            .join()
+            .await
+    });
+
+    assert!(res.is_some());
+}
+
+#[test]
+fn submit_policy_batch() {
+    setup_tracing();
+
+    inel::init(RingOptions::default().submit_policy(inel::SubmitPolicy::Tick));
+
+    let flushes = inel::block_on(async {
+        let before = inel::metrics().ring.flushes;
+
+        let sleeps = (0..8)
+            .map(|_| inel::spawn(inel::time::sleep(std::time::Duration::from_millis(1))).join())
+            .collect::<Vec<_>>();
+        futures::future::join_all(sleeps).await;
+
+        inel::metrics().ring.flushes - before
+    });
+
+    assert_eq!(flushes, 1);
+    assert!(inel::is_done());
+}
+
 #[test]
 fn main() {
     use core::sync::atomic::Ordering;
@@ -118,6 +310,40 @@ fn main() {
     assert!(CALLED.load(Ordering::SeqCst));
 }
 
+#[test]
+fn main_options() {
+    setup_tracing();
+
+    #[inel::main(submissions = 32, fixed_buffers = 4, sqpoll)]
+    async fn main() {
+        let metrics = inel::metrics();
+        assert_eq!(metrics.ring.fixed_buffers.capacity, 4);
+    }
+
+    main();
+}
+
+#[inel::test]
+async fn test_macro() {
+    inel::time::sleep(std::time::Duration::from_millis(1)).await;
+}
+
+#[inel::test(fixed_buffers = 2, timeout = 1000)]
+async fn test_macro_options() -> std::io::Result<()> {
+    assert_eq!(inel::metrics().ring.fixed_buffers.capacity, 2);
+
+    let buffer = inel::buffer::Fixed::new(64)?;
+    drop(buffer);
+
+    Ok(())
+}
+
+#[inel::test(timeout = 10)]
+#[should_panic(expected = "Test timed out")]
+async fn test_macro_timeout() {
+    inel::time::sleep(std::time::Duration::from_secs(1)).await;
+}
+
 #[test]
 fn select() {
     setup_tracing();
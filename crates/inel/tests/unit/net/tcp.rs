@@ -1,7 +1,9 @@
 use std::{
     io::{Read, Write},
-    os::fd::{FromRawFd, IntoRawFd},
+    mem::ManuallyDrop,
+    os::fd::{AsRawFd, FromRawFd, IntoRawFd},
     thread::JoinHandle,
+    time::Duration,
 };
 
 use futures::{select, AsyncBufReadExt, AsyncWriteExt, FutureExt, SinkExt, StreamExt};
@@ -195,6 +197,283 @@ fn error() {
     });
 }
 
+#[test]
+fn options() {
+    setup_tracing();
+
+    inel::block_on(async {
+        let listener = inel::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(listener.set_reuseaddr(true).await.is_ok());
+        assert!(listener.set_fastopen(16).await.is_ok());
+
+        let stream = inel::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+
+        assert!(stream.set_nodelay(true).await.is_ok());
+        assert!(stream.set_ttl(42).await.is_ok());
+        assert!(stream.set_recv_buffer_size(1 << 16).await.is_ok());
+        assert!(stream.set_send_buffer_size(1 << 16).await.is_ok());
+        assert!(stream
+            .set_linger(Some(Duration::from_secs(1)))
+            .await
+            .is_ok());
+
+        let keepalive = inel::net::TcpKeepalive::new()
+            .idle(Duration::from_secs(60))
+            .interval(Duration::from_secs(10))
+            .count(3);
+        assert!(stream.set_keepalive(Some(keepalive)).await.is_ok());
+
+        let std =
+            ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_fd(stream.as_raw_fd()) });
+        assert!(std.nodelay().unwrap());
+        assert_eq!(std.ttl().unwrap(), 42);
+
+        assert!(stream.set_keepalive(None).await.is_ok());
+        assert!(stream.set_nodelay(false).await.is_ok());
+        assert!(!std.nodelay().unwrap());
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn socket() {
+    setup_tracing();
+
+    inel::block_on(async {
+        let listener = inel::net::TcpSocket::new_v4()
+            .reuseaddr(true)
+            .reuseport(true)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .backlog(16)
+            .listen()
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let stream = inel::net::TcpSocket::new_v4()
+            .nodelay(true)
+            .ttl(42)
+            .bind("127.0.0.2:0".parse().unwrap())
+            .connect(addr)
+            .await
+            .unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        assert_eq!(
+            stream.local_addr().unwrap().ip(),
+            "127.0.0.2".parse::<std::net::IpAddr>().unwrap()
+        );
+
+        let std =
+            ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_fd(stream.as_raw_fd()) });
+        assert!(std.nodelay().unwrap());
+        assert_eq!(std.ttl().unwrap(), 42);
+
+        let (conn, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer, stream.local_addr().unwrap());
+        assert_eq!(conn.local_addr().unwrap(), addr);
+
+        let res = inel::net::TcpSocket::new_v6().connect(addr).await;
+        assert!(res.is_err());
+    });
+
+    assert!(inel::is_done());
+}
+
+fn drain_shard(mut incoming: inel::net::Incoming) -> usize {
+    inel::block_on(async move {
+        let mut accepted = 0;
+        loop {
+            select! {
+                conn = incoming.next().fuse() => {
+                    assert!(conn.unwrap().is_ok());
+                    accepted += 1;
+                }
+                _ = inel::time::sleep(Duration::from_millis(200)).fuse() => break,
+            }
+        }
+        accepted
+    })
+}
+
+#[test]
+fn sharded() {
+    setup_tracing();
+
+    let port = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let shards = inel::block_on(inel::net::TcpListener::bind_sharded(
+        ("127.0.0.1", port),
+        4,
+        inel::net::Steering::Hash,
+    ))
+    .unwrap();
+    assert_eq!(shards.len(), 4);
+
+    let clients = (0..32)
+        .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+        .collect::<Vec<_>>();
+
+    let handles = shards
+        .into_iter()
+        .map(|incoming| std::thread::spawn(move || drain_shard(incoming)))
+        .collect::<Vec<_>>();
+
+    let accepted: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(accepted, clients.len());
+
+    inel::block_on(async {
+        let listeners = inel::net::TcpSocket::new_v4()
+            .bind("127.0.0.1:0".parse().unwrap())
+            .listen_sharded(2, inel::net::Steering::Cpu)
+            .await
+            .unwrap();
+
+        let addr = listeners[0].local_addr().unwrap();
+        assert_eq!(listeners[1].local_addr().unwrap(), addr);
+
+        let mut incoming = futures::stream::select_all(listeners.into_iter().map(|l| l.incoming()));
+
+        let clients = (0..8)
+            .map(|_| std::net::TcpStream::connect(addr).unwrap())
+            .collect::<Vec<_>>();
+
+        for _ in 0..clients.len() {
+            assert!(incoming.next().await.unwrap().is_ok());
+        }
+
+        let res = inel::net::TcpSocket::new_v4()
+            .listen_sharded(0, inel::net::Steering::Hash)
+            .await;
+        assert!(res.is_err());
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn max_connections() {
+    setup_tracing();
+
+    inel::block_on(async {
+        let listener = inel::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut incoming = listener.incoming().with_max_connections(2);
+
+        let clients = (0..4)
+            .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+            .collect::<Vec<_>>();
+
+        let first = incoming.next().await.unwrap().unwrap();
+        let second = incoming.next().await.unwrap().unwrap();
+
+        select! {
+            _ = incoming.next().fuse() => panic!("accepted over the limit"),
+            _ = inel::time::sleep(Duration::from_millis(50)).fuse() => {}
+        }
+
+        let stats = incoming.stats();
+        assert_eq!(stats.active, 2);
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.paused, 1);
+
+        std::mem::drop(first);
+        let third = incoming.next().await.unwrap().unwrap();
+
+        std::mem::drop((second, third));
+        let fourth = incoming.next().await.unwrap().unwrap();
+        assert!(fourth.peer_addr().is_ok());
+
+        let stats = incoming.stats();
+        assert_eq!(stats.active, 1);
+        assert_eq!(stats.accepted, clients.len() as u64);
+        assert_eq!(stats.rejected, 0);
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn single_accept() {
+    setup_tracing();
+
+    inel::block_on(async {
+        let listener = inel::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut incoming = listener.incoming().with_multishot(false);
+
+        let clients = (0..4)
+            .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+            .collect::<Vec<_>>();
+
+        for client in clients.iter() {
+            let conn = incoming.next().await.unwrap().unwrap();
+            assert_eq!(conn.peer_addr().unwrap(), client.local_addr().unwrap());
+        }
+
+        let stats = incoming.stats();
+        assert_eq!(stats.active, 0);
+        assert_eq!(stats.accepted, clients.len() as u64);
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn single_accept_max_connections() {
+    setup_tracing();
+
+    inel::block_on(async {
+        let listener = inel::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut incoming = listener
+            .incoming()
+            .with_multishot(false)
+            .with_max_connections(1);
+
+        let clients = (0..3)
+            .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+            .collect::<Vec<_>>();
+
+        for client in clients.iter() {
+            let conn = incoming.next().await.unwrap().unwrap();
+            assert_eq!(conn.peer_addr().unwrap(), client.local_addr().unwrap());
+
+            select! {
+                _ = incoming.next().fuse() => panic!("accepted over the limit"),
+                _ = inel::time::sleep(Duration::from_millis(10)).fuse() => {}
+            }
+        }
+
+        let stats = incoming.stats();
+        assert_eq!(stats.active, 0);
+        assert_eq!(stats.accepted, clients.len() as u64);
+        assert_eq!(stats.rejected, 0);
+    });
+
+    assert!(inel::is_done());
+}
+
 #[test]
 fn raw_fd() {
     setup_tracing();
@@ -419,6 +698,177 @@ mod direct {
         assert!(inel::is_done());
     }
 
+    #[test]
+    fn options() {
+        setup_tracing();
+
+        inel::block_on(async {
+            let port = find_open_port();
+
+            let listener = inel::net::TcpListener::bind_direct(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            assert!(listener.set_reuseport(true).await.is_ok());
+            assert!(listener.set_nodelay(true).await.is_ok());
+
+            let stream = inel::net::TcpStream::connect_direct(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            assert!(stream.set_nodelay(true).await.is_ok());
+            assert!(stream.set_ttl(42).await.is_ok());
+            assert!(stream
+                .set_keepalive(Some(inel::net::TcpKeepalive::new().count(3)))
+                .await
+                .is_ok());
+
+            let regular = stream.make_regular().await.unwrap();
+            let std =
+                ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_fd(regular.as_raw_fd()) });
+            assert!(std.nodelay().unwrap());
+            assert_eq!(std.ttl().unwrap(), 42);
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    #[test_repeat(10)]
+    fn socket() {
+        setup_tracing();
+
+        inel::block_on(async {
+            let port = find_open_port();
+            let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+
+            let listener = inel::net::TcpSocket::new_v4()
+                .reuseaddr(true)
+                .nodelay(true)
+                .bind(addr)
+                .backlog(16)
+                .direct()
+                .listen()
+                .await
+                .unwrap();
+
+            let stream = inel::net::TcpSocket::new_v4()
+                .nodelay(true)
+                .keepalive(Some(inel::net::TcpKeepalive::new().count(3)))
+                .bind("127.0.0.2:0".parse().unwrap())
+                .direct()
+                .connect(addr)
+                .await
+                .unwrap();
+
+            let (conn, peer) = listener.accept().await.unwrap();
+            assert_eq!(peer.ip(), "127.0.0.2".parse::<std::net::IpAddr>().unwrap());
+
+            let regular = stream.make_regular().await.unwrap();
+            let std =
+                ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_fd(regular.as_raw_fd()) });
+            assert!(std.nodelay().unwrap());
+            assert_eq!(std.local_addr().unwrap(), peer);
+
+            std::mem::drop(conn);
+
+            let res = inel::net::TcpSocket::new_v4()
+                .bind(addr)
+                .direct()
+                .listen()
+                .await;
+            assert!(res.is_err());
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn socket_invalid_option() {
+        setup_tracing();
+
+        inel::block_on(async {
+            // rejected by the kernel, which also goes through the fallback for unsupported options
+            let res = inel::net::TcpSocket::new_v4()
+                .keepalive(Some(inel::net::TcpKeepalive::new().count(0)))
+                .bind("127.0.0.1:0".parse().unwrap())
+                .direct()
+                .listen()
+                .await;
+
+            assert_eq!(res.err().unwrap().raw_os_error(), Some(libc::EINVAL));
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn max_connections() {
+        setup_tracing();
+
+        inel::block_on(async {
+            let port = find_open_port();
+
+            let listener = inel::net::TcpListener::bind_direct(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            let mut incoming = listener.incoming().with_max_connections(1);
+
+            let clients = (0..3)
+                .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+                .collect::<Vec<_>>();
+
+            for _ in 0..clients.len() {
+                let conn = incoming.next().await.unwrap().unwrap();
+
+                select! {
+                    _ = incoming.next().fuse() => panic!("accepted over the limit"),
+                    _ = inel::time::sleep(Duration::from_millis(20)).fuse() => {}
+                }
+
+                std::mem::drop(conn);
+            }
+
+            let stats = incoming.stats();
+            assert_eq!(stats.active, 0);
+            assert_eq!(stats.accepted, clients.len() as u64);
+            assert!(stats.paused >= 1);
+        });
+
+        assert!(inel::is_done());
+    }
+
+    #[test]
+    fn single_accept() {
+        setup_tracing();
+
+        inel::block_on(async {
+            let port = find_open_port();
+
+            let listener = inel::net::TcpListener::bind_direct(("127.0.0.1", port))
+                .await
+                .unwrap();
+
+            let mut incoming = listener.incoming().with_multishot(false);
+
+            let clients = (0..4)
+                .map(|_| std::net::TcpStream::connect(("127.0.0.1", port)).unwrap())
+                .collect::<Vec<_>>();
+
+            let mut conns = Vec::new();
+            for _ in 0..clients.len() {
+                conns.push(incoming.next().await.unwrap().unwrap());
+            }
+
+            let stats = incoming.stats();
+            assert_eq!(stats.active, clients.len());
+            assert_eq!(stats.accepted, clients.len() as u64);
+        });
+
+        assert!(inel::is_done());
+    }
+
     #[test]
     #[test_repeat(10)]
     fn client() {
@@ -0,0 +1,299 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use inel::{RingOptions, Runtime};
+
+use crate::helpers::setup_tracing;
+
+fn fixed_buffers() -> u32 {
+    inel::metrics().ring.fixed_buffers.capacity
+}
+
+#[test]
+fn block_on() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default().fixed_buffers(8)).unwrap();
+
+    let res = runtime.block_on(async {
+        inel::time::sleep(Duration::from_millis(1)).await;
+        fixed_buffers()
+    });
+
+    assert_eq!(res, 8);
+    assert!(runtime.is_done());
+}
+
+#[test]
+fn sequence() {
+    setup_tracing();
+
+    for capacity in [4, 16, 32] {
+        let runtime = Runtime::new(RingOptions::default().fixed_buffers(capacity)).unwrap();
+
+        let handle = runtime.spawn(async { fixed_buffers() });
+        let res = runtime.block_on(async move { handle.join().await });
+
+        assert_eq!(res, Some(capacity));
+    }
+}
+
+#[test]
+fn enter() {
+    setup_tracing();
+
+    let global = fixed_buffers();
+    let runtime = Runtime::new(RingOptions::default().fixed_buffers(global + 1)).unwrap();
+
+    {
+        let _guard = runtime.enter();
+        assert_eq!(fixed_buffers(), global + 1);
+
+        let _nested = runtime.enter();
+        assert_eq!(fixed_buffers(), global + 1);
+
+        let other = Runtime::new(RingOptions::default().fixed_buffers(global + 2)).unwrap();
+        assert_eq!(other.block_on(async { fixed_buffers() }), global + 2);
+
+        assert_eq!(fixed_buffers(), global + 1);
+    }
+
+    assert_eq!(fixed_buffers(), global);
+}
+
+#[test]
+fn drop() {
+    setup_tracing();
+
+    let done = Rc::new(Cell::new(false));
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+    runtime.spawn({
+        let done = done.clone();
+        async move {
+            inel::time::sleep(Duration::from_millis(10)).await;
+            done.set(true);
+        }
+    });
+
+    std::mem::drop(runtime);
+
+    assert!(!done.get());
+    assert!(inel::is_done());
+}
+
+#[test]
+fn drop_pending() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+    runtime.spawn(async {
+        loop {
+            inel::time::sleep(Duration::from_secs(60)).await;
+        }
+    });
+
+    let start = std::time::Instant::now();
+    std::mem::drop(runtime);
+
+    assert!(start.elapsed() < inel::SHUTDOWN_TIMEOUT);
+    assert!(inel::is_done());
+}
+
+#[test]
+fn drain() {
+    setup_tracing();
+
+    let done = Rc::new(Cell::new(false));
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+    runtime.spawn({
+        let done = done.clone();
+        async move {
+            inel::time::sleep(Duration::from_millis(10)).await;
+            done.set(true);
+        }
+    });
+
+    assert!(runtime.drain());
+    assert!(done.get());
+}
+
+#[test]
+fn external_loop() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+
+    let done = Rc::new(Cell::new(false));
+    let done_clone = done.clone();
+    runtime.spawn(async move {
+        inel::time::sleep(Duration::from_millis(10)).await;
+        done_clone.set(true);
+    });
+
+    let fd = {
+        let _guard = runtime.enter();
+        inel::ring_fd()
+    };
+
+    // drive the runtime like another event loop would, blocking on the ring descriptor
+    let mut turns = 0;
+    while runtime.run_until_stalled() {
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let res = unsafe { libc::poll(&mut pollfd, 1, 1000) };
+        assert_eq!(res, 1);
+        turns += 1;
+    }
+
+    assert!(done.get());
+    assert!(turns >= 1);
+    assert!(runtime.is_done());
+}
+
+#[test]
+fn turn() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+
+    runtime.spawn(async {
+        inel::time::sleep(Duration::from_millis(50)).await;
+    });
+
+    assert!(runtime.turn(Duration::from_millis(1)));
+
+    let start = std::time::Instant::now();
+    assert!(runtime.turn(Duration::from_millis(5)));
+    assert!(start.elapsed() < Duration::from_millis(40));
+
+    while runtime.turn(Duration::from_millis(100)) {}
+    assert!(runtime.is_done());
+}
+
+#[test]
+fn hooks() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+
+    let idle = Rc::new(Cell::new(0));
+    let parked = Rc::new(Cell::new(0));
+    let unparked = Rc::new(Cell::new(0));
+
+    let maintenance = Rc::new(Cell::new(false));
+
+    runtime.block_on({
+        let (idle, parked, unparked) = (idle.clone(), parked.clone(), unparked.clone());
+        let maintenance = maintenance.clone();
+        async move {
+            inel::on_idle(move || {
+                idle.set(idle.get() + 1);
+                if idle.get() == 1 {
+                    let maintenance = maintenance.clone();
+                    inel::spawn(async move { maintenance.set(true) });
+                }
+            });
+            inel::on_park(move || parked.set(parked.get() + 1));
+            inel::on_unpark(move || unparked.set(unparked.get() + 1));
+
+            inel::time::sleep(Duration::from_millis(5)).await;
+        }
+    });
+
+    assert!(maintenance.get());
+    assert!(idle.get() >= 2);
+    assert!(parked.get() >= 1);
+    assert_eq!(parked.get(), unparked.get());
+}
+
+#[test]
+fn shutdown() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default()).unwrap();
+
+    let dropped = Rc::new(Cell::new(false));
+    runtime.spawn({
+        let dropped = dropped.clone();
+        async move {
+            let _guard = Guard(dropped);
+            inel::time::sleep(Duration::from_secs(3600)).await;
+        }
+    });
+
+    runtime.spawn(async {
+        let listener = inel::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let _ = listener.accept().await;
+    });
+
+    assert!(runtime.run_until_stalled());
+
+    let report = runtime.shutdown(Duration::from_secs(1));
+
+    assert!(dropped.get());
+    assert_eq!(report.aborted, 2);
+    assert!(report.is_clean(), "{report:?}");
+}
+
+#[test]
+fn shutdown_leak() {
+    setup_tracing();
+
+    let runtime = Runtime::new(RingOptions::default().fixed_buffers(4)).unwrap();
+
+    runtime.block_on(async {
+        std::mem::forget(inel::buffer::Fixed::new(64).unwrap());
+    });
+
+    let report = runtime.shutdown(Duration::from_millis(100));
+
+    assert_eq!(report.aborted, 0);
+    assert_eq!(report.fixed_buffers, 1);
+    assert!(!report.is_clean());
+}
+
+struct Guard(Rc<Cell<bool>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn run_until_stalled_defer_taskrun() {
+    setup_tracing();
+
+    let options = RingOptions::default()
+        .single_issuer(true)
+        .defer_taskrun(true);
+    let runtime = Runtime::new(options).unwrap();
+
+    let done = Rc::new(Cell::new(false));
+    runtime.spawn({
+        let done = done.clone();
+        async move {
+            inel::time::sleep(Duration::from_millis(5)).await;
+            done.set(true);
+        }
+    });
+
+    // completions of deferred task work are only posted when polling asks for them
+    let start = std::time::Instant::now();
+    while runtime.run_until_stalled() {
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "completion never seen"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    assert!(done.get());
+    assert!(runtime.is_done());
+}
@@ -0,0 +1,130 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use inel::{
+    sync::{AtomicSemaphore, AtomicWaitCell},
+    time::sleep,
+};
+
+use crate::helpers::setup_tracing;
+
+fn millis(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn wait_cell() {
+    setup_tracing();
+
+    let cell = Arc::new(AtomicWaitCell::new(0));
+
+    let waiters = (0..4)
+        .map(|i| {
+            let cell = cell.clone();
+            thread::spawn(move || inel::block_on(async move { cell.wait_until(|v| v > i).await }))
+        })
+        .collect::<Vec<_>>();
+
+    for i in 1..=4 {
+        thread::sleep(millis(2));
+        cell.store(i);
+    }
+
+    for (i, waiter) in waiters.into_iter().enumerate() {
+        assert!(waiter.join().unwrap() > i as u32);
+    }
+}
+
+#[test]
+fn wait_cell_local() {
+    setup_tracing();
+
+    let cell = Arc::new(AtomicWaitCell::new(0));
+
+    let value = inel::block_on({
+        let cell = cell.clone();
+        async move {
+            inel::spawn({
+                let cell = cell.clone();
+                async move {
+                    sleep(millis(1)).await;
+                    cell.fetch_add(5);
+                }
+            });
+
+            cell.wait(0).await
+        }
+    });
+
+    assert_eq!(value, 5);
+    assert_eq!(cell.compare_exchange(5, 6), Ok(5));
+    assert_eq!(cell.compare_exchange(5, 7), Err(6));
+    assert_eq!(cell.swap(1), 6);
+}
+
+#[test]
+fn wait_cell_cancel() {
+    setup_tracing();
+
+    let cell = AtomicWaitCell::new(0);
+
+    inel::block_on(async move {
+        assert!(inel::time::timeout(millis(1), cell.wait(0)).await.is_err());
+
+        cell.store(1);
+        assert_eq!(cell.wait(0).await, 1);
+    });
+
+    assert!(inel::is_done());
+}
+
+#[test]
+fn semaphore() {
+    setup_tracing();
+
+    let semaphore = Arc::new(AtomicSemaphore::new(2));
+    let active = Arc::new(AtomicUsize::new(0));
+    let max = Arc::new(AtomicUsize::new(0));
+
+    let workers = (0..4)
+        .map(|_| {
+            let (semaphore, active, max) = (semaphore.clone(), active.clone(), max.clone());
+            thread::spawn(move || {
+                inel::block_on(async move {
+                    for _ in 0..5 {
+                        let _permit = semaphore.acquire().await;
+                        let current = active.fetch_add(1, Ordering::AcqRel) + 1;
+                        max.fetch_max(current, Ordering::AcqRel);
+                        sleep(millis(1)).await;
+                        active.fetch_sub(1, Ordering::AcqRel);
+                    }
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    workers.into_iter().for_each(|w| w.join().unwrap());
+
+    assert!(max.load(Ordering::Acquire) <= 2);
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[test]
+fn semaphore_try() {
+    setup_tracing();
+
+    let semaphore = AtomicSemaphore::new(3);
+
+    let permit = semaphore.try_acquire_many(2).unwrap();
+    assert!(semaphore.try_acquire_many(2).is_none());
+    assert_eq!(semaphore.try_acquire().unwrap().num_permits(), 1);
+
+    permit.forget();
+    assert_eq!(semaphore.available_permits(), 1);
+}
@@ -0,0 +1,368 @@
+use std::{rc::Rc, thread, time::Duration};
+
+use futures::FutureExt;
+use inel::{
+    sync::{broadcast, mpsc, oneshot, watch, RecvError, SendError, TryRecvError, TrySendError},
+    time::sleep,
+};
+
+use crate::helpers::setup_tracing;
+
+fn millis(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn mpsc_bounded() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::channel(2);
+
+    inel::spawn(async move {
+        for i in 0..10 {
+            send.send(i).await.unwrap();
+        }
+    });
+
+    let received = inel::block_on(async move {
+        let mut received = Vec::new();
+        while let Some(value) = recv.recv().await {
+            assert!(recv.len() <= 2);
+            received.push(value);
+            sleep(millis(1)).await;
+        }
+        received
+    });
+
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn mpsc_try() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::channel(1);
+
+    assert_eq!(recv.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(send.try_send(1), Ok(()));
+    assert_eq!(send.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(recv.try_recv(), Ok(1));
+
+    let other = send.clone();
+    drop(send);
+    assert_eq!(other.try_send(3), Ok(()));
+    drop(other);
+
+    assert_eq!(recv.try_recv(), Ok(3));
+    assert_eq!(recv.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+fn mpsc_closed() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::unbounded_channel();
+
+    for i in 0..100 {
+        send.try_send(i).unwrap();
+    }
+
+    recv.close();
+    assert!(send.is_closed());
+    assert_eq!(send.try_send(100), Err(TrySendError::Closed(100)));
+    assert_eq!(recv.len(), 100);
+
+    // buffered values are still received, then the channel reports closed
+    // even though the sender is alive
+    for i in 0..100 {
+        assert_eq!(recv.try_recv(), Ok(i));
+    }
+    assert_eq!(recv.try_recv(), Err(TryRecvError::Closed));
+
+    let res = inel::block_on(async move { inel::time::timeout(millis(200), recv.recv()).await });
+    assert_eq!(res.ok(), Some(None));
+    drop(send);
+
+    let (send, recv) = mpsc::channel(1);
+    send.try_send(Rc::new(0)).unwrap();
+    drop(recv);
+
+    let res = inel::block_on(async move { send.send(Rc::new(1)).await });
+    assert!(matches!(res, Err(SendError(value)) if *value == 1));
+}
+
+#[test]
+fn mpsc_cancel() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::channel(1);
+
+    inel::block_on(async move {
+        assert!(inel::time::timeout(millis(1), recv.recv()).await.is_err());
+
+        send.send(1).await.unwrap();
+        assert!(inel::time::timeout(millis(1), send.send(2)).await.is_err());
+
+        assert_eq!(recv.recv().await, Some(1));
+        assert!(recv.recv().now_or_never().is_none());
+
+        send.send(3).await.unwrap();
+        assert_eq!(recv.recv().await, Some(3));
+    });
+}
+
+#[test]
+fn mpsc_shared() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::shared::channel(4);
+
+    let producers = (0..4)
+        .map(|i| {
+            let send = send.clone();
+            thread::spawn(move || {
+                inel::block_on(async move {
+                    for j in 0..50 {
+                        send.send(i * 100 + j).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    drop(send);
+
+    let mut received = inel::block_on(async move {
+        let mut received = Vec::new();
+        while let Some(value) = recv.recv().await {
+            received.push(value);
+        }
+        received
+    });
+
+    producers.into_iter().for_each(|p| p.join().unwrap());
+
+    received.sort();
+    let expected = (0..4)
+        .flat_map(|i| (0..50).map(move |j| i * 100 + j))
+        .collect::<Vec<_>>();
+    assert_eq!(received, expected);
+}
+
+#[test]
+fn mpsc_shared_waiting() {
+    setup_tracing();
+
+    let (send, mut recv) = mpsc::shared::unbounded_channel();
+
+    let consumer = thread::spawn(move || {
+        inel::block_on(async move {
+            let mut sum = 0;
+            while let Some(value) = recv.recv().await {
+                sum += value;
+            }
+            sum
+        })
+    });
+
+    for i in 0..10 {
+        thread::sleep(millis(1));
+        send.try_send(i).unwrap();
+    }
+    drop(send);
+
+    assert_eq!(consumer.join().unwrap(), 45);
+}
+
+#[test]
+fn oneshot() {
+    setup_tracing();
+
+    let (send, mut recv) = oneshot::channel();
+
+    inel::spawn(async move {
+        sleep(millis(1)).await;
+        send.send(10).unwrap();
+    });
+
+    assert_eq!(inel::block_on(async move { recv.recv().await }), Ok(10));
+
+    let (send, mut recv) = oneshot::channel::<i32>();
+    assert_eq!(recv.try_recv(), Err(TryRecvError::Empty));
+    drop(send);
+    assert_eq!(recv.try_recv(), Err(TryRecvError::Closed));
+
+    let (send, mut recv) = oneshot::channel();
+    recv.close();
+    assert!(send.is_closed());
+    assert_eq!(send.send(1), Err(1));
+}
+
+#[test]
+fn oneshot_shared() {
+    setup_tracing();
+
+    let (send, mut recv) = oneshot::shared::channel();
+
+    let handle = thread::spawn(move || {
+        thread::sleep(millis(5));
+        send.send(String::from("hello")).unwrap();
+    });
+
+    let res = inel::block_on(async move { recv.recv().await });
+    assert_eq!(res.as_deref(), Ok("hello"));
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn broadcast() {
+    setup_tracing();
+
+    let (send, recv) = broadcast::channel(16);
+
+    let receivers = (0..4)
+        .map(|_| {
+            let mut recv = recv.clone();
+            inel::spawn(async move {
+                let mut received = Vec::new();
+                while let Ok(value) = recv.recv().await {
+                    received.push(value);
+                }
+                received
+            })
+        })
+        .collect::<Vec<_>>();
+
+    drop(recv);
+
+    inel::block_on(async move {
+        for i in 0..10 {
+            assert_eq!(send.send(i), Ok(4));
+            sleep(millis(1)).await;
+        }
+        drop(send);
+
+        for receiver in receivers {
+            assert_eq!(receiver.join().await.unwrap(), (0..10).collect::<Vec<_>>());
+        }
+    });
+}
+
+#[test]
+fn broadcast_lagged() {
+    setup_tracing();
+
+    let (send, mut recv) = broadcast::channel(2);
+
+    for i in 0..5 {
+        send.send(i).unwrap();
+    }
+
+    let mut late = send.subscribe();
+    assert_eq!(late.try_recv(), Err(broadcast::TryRecvError::Empty));
+
+    assert_eq!(recv.try_recv(), Err(broadcast::TryRecvError::Lagged(3)));
+    assert_eq!(recv.try_recv(), Ok(3));
+    assert_eq!(recv.try_recv(), Ok(4));
+
+    drop(recv);
+    drop(late);
+    assert_eq!(send.receiver_count(), 0);
+    assert_eq!(send.send(5), Err(SendError(5)));
+}
+
+#[test]
+fn broadcast_shared() {
+    setup_tracing();
+
+    let (send, recv) = broadcast::shared::channel(64);
+
+    let receivers = (0..2)
+        .map(|_| {
+            let mut recv = recv.clone();
+            thread::spawn(move || {
+                inel::block_on(async move {
+                    let mut sum = 0;
+                    loop {
+                        match recv.recv().await {
+                            Ok(value) => sum += value,
+                            Err(broadcast::RecvError::Closed) => return sum,
+                            Err(err) => panic!("{err}"),
+                        }
+                    }
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    drop(recv);
+
+    for i in 0..10 {
+        thread::sleep(millis(1));
+        send.send(i).unwrap();
+    }
+    drop(send);
+
+    for receiver in receivers {
+        assert_eq!(receiver.join().unwrap(), 45);
+    }
+}
+
+#[test]
+fn watch() {
+    setup_tracing();
+
+    let (send, mut recv) = watch::channel(0);
+
+    assert_eq!(recv.has_changed(), Ok(false));
+    send.send(1).unwrap();
+    send.send(2).unwrap();
+    assert_eq!(recv.has_changed(), Ok(true));
+    assert_eq!(recv.get_and_update(), 2);
+    assert_eq!(recv.has_changed(), Ok(false));
+
+    let mut other = send.subscribe();
+    inel::spawn(async move {
+        for i in 3..6 {
+            sleep(millis(1)).await;
+            send.send_modify(|value| *value = i);
+        }
+    });
+
+    let seen = inel::block_on(async move {
+        let mut seen = Vec::new();
+        while other.changed().await.is_ok() {
+            seen.push(other.get_and_update());
+        }
+        seen
+    });
+
+    assert_eq!(seen, vec![3, 4, 5]);
+    assert_eq!(recv.get(), 5);
+    assert_eq!(recv.has_changed(), Err(RecvError));
+}
+
+#[test]
+fn watch_shared() {
+    setup_tracing();
+
+    let (send, mut recv) = watch::shared::channel(String::new());
+
+    let watcher = thread::spawn(move || {
+        inel::block_on(async move {
+            while recv.with(|value| value.len()) < 3 {
+                recv.changed().await.unwrap();
+            }
+            recv.get()
+        })
+    });
+
+    for c in ['a', 'b', 'c'] {
+        thread::sleep(millis(1));
+        send.send_modify(|value| value.push(c));
+    }
+
+    assert_eq!(watcher.join().unwrap(), "abc");
+    assert_eq!(send.send_replace(String::new()), "abc");
+}
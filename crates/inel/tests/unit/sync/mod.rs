@@ -0,0 +1,3 @@
+mod atomic;
+mod channel;
+mod primitives;
@@ -0,0 +1,329 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use futures::{future, FutureExt};
+use inel::{
+    sync::{Barrier, Mutex, Notify, RwLock, Semaphore},
+    time::sleep,
+};
+
+use crate::helpers::setup_tracing;
+
+fn millis(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn mutex() {
+    setup_tracing();
+
+    let mutex = Rc::new(Mutex::new(Vec::new()));
+
+    for i in 0..10 {
+        let mutex = mutex.clone();
+        inel::spawn(async move {
+            let mut guard = mutex.lock().await;
+            sleep(millis(1)).await;
+            guard.push(i);
+        });
+    }
+
+    inel::run();
+
+    let mutex = Rc::into_inner(mutex).unwrap();
+    assert_eq!(mutex.into_inner(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn mutex_try_lock() {
+    setup_tracing();
+
+    let mut mutex = Mutex::new(1);
+
+    {
+        let guard = mutex.try_lock().unwrap();
+        assert_eq!(*guard, 1);
+        assert!(mutex.try_lock().is_none());
+    }
+
+    *mutex.get_mut() += 1;
+    assert_eq!(*mutex.try_lock().unwrap(), 2);
+}
+
+#[test]
+fn mutex_cancel() {
+    setup_tracing();
+
+    let mutex = Rc::new(Mutex::new(0));
+
+    inel::block_on({
+        let mutex = mutex.clone();
+        async move {
+            let guard = mutex.lock().await;
+
+            let res = inel::time::timeout(millis(5), mutex.lock()).await;
+            assert!(res.is_err());
+
+            let waiting = inel::spawn({
+                let mutex = mutex.clone();
+                async move { *mutex.lock().await += 1 }
+            });
+
+            sleep(millis(1)).await;
+            drop(guard);
+
+            waiting.join().await.unwrap();
+        }
+    });
+
+    assert_eq!(*mutex.try_lock().unwrap(), 1);
+}
+
+#[test]
+fn rwlock() {
+    setup_tracing();
+
+    let lock = Rc::new(RwLock::new(0));
+    let readers = Rc::new(Cell::new(0));
+    let max = Rc::new(Cell::new(0));
+
+    for i in 0..12 {
+        let (lock, readers, max) = (lock.clone(), readers.clone(), max.clone());
+        inel::spawn(async move {
+            if i % 4 == 0 {
+                let mut guard = lock.write().await;
+                assert_eq!(readers.get(), 0);
+                sleep(millis(1)).await;
+                *guard += 1;
+            } else {
+                let _guard = lock.read().await;
+                readers.set(readers.get() + 1);
+                max.set(max.get().max(readers.get()));
+                sleep(millis(1)).await;
+                readers.set(readers.get() - 1);
+            }
+        });
+    }
+
+    inel::run();
+
+    assert_eq!(*lock.try_read().unwrap(), 3);
+    assert_eq!(max.get(), 3);
+}
+
+#[test]
+fn rwlock_try() {
+    setup_tracing();
+
+    let lock = RwLock::new(0);
+
+    let read = lock.try_read().unwrap();
+    assert!(lock.try_read().is_some());
+    assert!(lock.try_write().is_none());
+    drop(read);
+
+    let mut write = lock.try_write().unwrap();
+    *write = 5;
+    assert!(lock.try_read().is_none());
+    drop(write);
+
+    assert_eq!(lock.into_inner(), 5);
+}
+
+#[test]
+fn semaphore() {
+    setup_tracing();
+
+    let semaphore = Rc::new(Semaphore::new(3));
+    let active = Rc::new(Cell::new(0));
+    let max = Rc::new(Cell::new(0));
+
+    for _ in 0..20 {
+        let (semaphore, active, max) = (semaphore.clone(), active.clone(), max.clone());
+        inel::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            active.set(active.get() + 1);
+            max.set(max.get().max(active.get()));
+            sleep(millis(1)).await;
+            active.set(active.get() - 1);
+        });
+    }
+
+    inel::run();
+
+    assert_eq!(max.get(), 3);
+    assert_eq!(semaphore.available_permits(), 3);
+}
+
+#[test]
+fn semaphore_fifo() {
+    setup_tracing();
+
+    let semaphore = Rc::new(Semaphore::new(2));
+    let order = Rc::new(RefCell::new(Vec::new()));
+
+    inel::block_on({
+        let (semaphore, order) = (semaphore.clone(), order.clone());
+        async move {
+            let permit = semaphore.acquire_many(2).await;
+
+            for (i, permits) in [2, 1, 1].into_iter().enumerate() {
+                let (semaphore, order) = (semaphore.clone(), order.clone());
+                inel::spawn(async move {
+                    let _permit = semaphore.acquire_many(permits).await;
+                    order.borrow_mut().push(i);
+                    sleep(millis(1)).await;
+                });
+            }
+
+            sleep(millis(1)).await;
+            assert!(semaphore.try_acquire().is_none());
+
+            drop(permit);
+        }
+    });
+
+    assert_eq!(*order.borrow(), vec![0, 1, 2]);
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[test]
+fn semaphore_cancel() {
+    setup_tracing();
+
+    let semaphore = Rc::new(Semaphore::new(1));
+
+    inel::block_on({
+        let semaphore = semaphore.clone();
+        async move {
+            let permit = semaphore.acquire().await;
+
+            // a large waiter at the front blocks the smaller ones until it is cancelled
+            let mut large = Box::pin(semaphore.acquire_many(2));
+            assert!((&mut large).now_or_never().is_none());
+
+            let small = inel::spawn({
+                let semaphore = semaphore.clone();
+                async move { semaphore.acquire().await.num_permits() }
+            });
+
+            drop(permit);
+            sleep(millis(1)).await;
+            assert_eq!(semaphore.available_permits(), 1);
+
+            drop(large);
+            assert_eq!(small.join().await, Some(1));
+        }
+    });
+
+    assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[test]
+fn semaphore_cancel_assigned() {
+    setup_tracing();
+
+    let semaphore = Semaphore::new(0);
+
+    let mut acquire = Box::pin(semaphore.acquire());
+    assert!((&mut acquire).now_or_never().is_none());
+
+    semaphore.add_permits(1);
+    assert_eq!(semaphore.available_permits(), 0);
+
+    drop(acquire);
+    assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[test]
+fn notify() {
+    setup_tracing();
+
+    let notify = Rc::new(Notify::new());
+    let woken = Rc::new(Cell::new(0));
+
+    inel::block_on({
+        let (notify, woken) = (notify.clone(), woken.clone());
+        async move {
+            notify.notify_one();
+            notify.notified().await;
+
+            for _ in 0..5 {
+                let (notify, woken) = (notify.clone(), woken.clone());
+                inel::spawn(async move {
+                    notify.notified().await;
+                    woken.set(woken.get() + 1);
+                });
+            }
+
+            sleep(millis(1)).await;
+            notify.notify_one();
+            sleep(millis(1)).await;
+            assert_eq!(woken.get(), 1);
+
+            notify.notify_waiters();
+        }
+    });
+
+    assert_eq!(woken.get(), 5);
+    assert!(notify.notified().now_or_never().is_none());
+}
+
+#[test]
+fn notify_cancel() {
+    setup_tracing();
+
+    let notify = Notify::new();
+
+    let mut first = Box::pin(notify.notified());
+    let mut second = Box::pin(notify.notified());
+    assert!((&mut first).now_or_never().is_none());
+    assert!((&mut second).now_or_never().is_none());
+
+    notify.notify_one();
+    drop(first);
+
+    assert!(second.now_or_never().is_some());
+    assert!(notify.notified().now_or_never().is_none());
+}
+
+#[test]
+fn barrier() {
+    setup_tracing();
+
+    let barrier = Rc::new(Barrier::new(4));
+    let leaders = Rc::new(Cell::new(0));
+
+    for round in 0..3 {
+        for i in 0..4 {
+            let (barrier, leaders) = (barrier.clone(), leaders.clone());
+            inel::spawn(async move {
+                sleep(millis(i)).await;
+                if barrier.wait().await.is_leader() {
+                    leaders.set(leaders.get() + 1);
+                }
+            });
+        }
+
+        inel::run();
+        assert_eq!(leaders.get(), round + 1);
+    }
+}
+
+#[test]
+fn barrier_cancel() {
+    setup_tracing();
+
+    let barrier = Barrier::new(2);
+
+    inel::block_on(async move {
+        let res = inel::time::timeout(millis(1), barrier.wait()).await;
+        assert!(res.is_err());
+
+        let results = future::join(barrier.wait(), barrier.wait()).await;
+        assert!(results.0.is_leader() ^ results.1.is_leader());
+    });
+}
@@ -0,0 +1,340 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use inel::{
+    task::{yield_now, JoinSet, Priority},
+    time::sleep,
+};
+
+use crate::helpers::setup_tracing;
+
+fn millis(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+struct Guard(Rc<Cell<bool>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn join_set() {
+    setup_tracing();
+
+    let finished = inel::block_on(async {
+        let mut set = JoinSet::new();
+        for i in [3, 1, 4, 2] {
+            set.spawn(async move {
+                sleep(millis(i * 2)).await;
+                i
+            });
+        }
+
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.try_join_next(), None);
+
+        let mut finished = Vec::new();
+        while let Some(res) = set.join_next().await {
+            finished.push(res.unwrap());
+        }
+
+        assert!(set.is_empty());
+        finished
+    });
+
+    assert_eq!(finished, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn join_set_abort() {
+    setup_tracing();
+
+    let dropped = Rc::new(Cell::new(false));
+
+    let results = inel::block_on({
+        let dropped = dropped.clone();
+        async move {
+            let mut set = JoinSet::new();
+
+            let guard = Guard(dropped);
+            let slow = set.spawn(async move {
+                let _guard = guard;
+                sleep(millis(1000)).await;
+                0
+            });
+
+            set.spawn(async { 1 });
+            set.insert(inel::spawn(async { 2 }));
+
+            slow.abort();
+            set.join_all().await
+        }
+    });
+
+    assert_eq!(results, vec![1, 2]);
+    assert!(dropped.get());
+    assert!(inel::is_done());
+}
+
+#[test]
+fn join_set_drop() {
+    setup_tracing();
+
+    let dropped = Rc::new(Cell::new(false));
+
+    inel::block_on({
+        let dropped = dropped.clone();
+        async move {
+            let mut set = JoinSet::new();
+            for _ in 0..4 {
+                let guard = Guard(dropped.clone());
+                set.spawn(async move {
+                    let _guard = guard;
+                    sleep(millis(1000)).await;
+                });
+            }
+
+            sleep(millis(1)).await;
+        }
+    });
+
+    assert!(dropped.get());
+    assert!(inel::is_done());
+}
+
+#[test]
+fn join_set_detach() {
+    setup_tracing();
+
+    let finished = Rc::new(Cell::new(false));
+
+    inel::block_on({
+        let finished = finished.clone();
+        async move {
+            let mut set = JoinSet::new();
+            set.spawn(async move {
+                sleep(millis(5)).await;
+                finished.set(true);
+            });
+
+            set.detach_all();
+            assert!(set.is_empty());
+        }
+    });
+
+    assert!(finished.get());
+    assert!(inel::is_done());
+}
+
+#[test]
+fn select() {
+    setup_tracing();
+
+    let dropped = Rc::new(Cell::new(false));
+
+    let res = inel::block_on({
+        let dropped = dropped.clone();
+        async move {
+            let guard = Guard(dropped.clone());
+
+            inel::select! {
+                _ = async move {
+                    let _guard = guard;
+                    sleep(millis(1000)).await;
+                } => unreachable!(),
+                value = async {
+                    sleep(millis(1)).await;
+                    5
+                } => {
+                    // the other branch is dropped before the handler runs
+                    assert!(dropped.get());
+                    value
+                }
+            }
+        }
+    });
+
+    assert_eq!(res, 5);
+    assert!(inel::is_done());
+}
+
+#[test]
+fn select_disabled() {
+    setup_tracing();
+
+    let res = inel::block_on(async {
+        inel::select! {
+            Some(value) = async { None::<i32> } => value,
+            Ok(value) = async { Err::<i32, ()>(()) } => value,
+            else => 0,
+        }
+    });
+
+    assert_eq!(res, 0);
+}
+
+#[test]
+fn select_biased() {
+    setup_tracing();
+
+    let res = inel::block_on(async {
+        inel::select! {
+            Some(value) = async { None::<i32> } => value,
+            value = async { 1 } => value + 10,
+            value = async { 2 } => value + 20,
+        }
+    });
+
+    assert_eq!(res, 11);
+}
+
+#[test]
+fn select_binding_modes() {
+    setup_tracing();
+
+    let res = inel::block_on(async {
+        inel::select! {
+            Some(mut line) = async { Some(String::from("select")) } => {
+                line.push('!');
+                line
+            },
+            Some(ref line) = async { Some(String::new()) } => line.clone(),
+        }
+    });
+
+    assert_eq!(res, "select!");
+}
+
+#[test]
+fn join() {
+    setup_tracing();
+
+    let res = inel::block_on(async {
+        inel::join!(
+            async {
+                sleep(millis(2)).await;
+                1
+            },
+            async { "two" },
+            sleep(millis(1)),
+        )
+    });
+
+    assert_eq!(res, (1, "two", ()));
+}
+
+inel::task_local! {
+    static REQUEST: u64;
+    static TENANT: String;
+}
+
+#[test]
+fn task_local_scope() {
+    setup_tracing();
+
+    let seen = inel::block_on(async {
+        let mut set = JoinSet::new();
+        for id in 0..4 {
+            set.spawn(REQUEST.scope(id, async move {
+                sleep(millis(4 - id)).await;
+                let inner = TENANT
+                    .scope(format!("tenant-{id}"), async {
+                        sleep(millis(1)).await;
+                        TENANT.with(|tenant| format!("{tenant}/{}", REQUEST.get()))
+                    })
+                    .await;
+
+                assert!(TENANT.try_with(|_| ()).is_err());
+                inner
+            }));
+        }
+
+        let mut seen = set.join_all().await;
+        seen.sort();
+        seen
+    });
+
+    assert_eq!(
+        seen,
+        vec!["tenant-0/0", "tenant-1/1", "tenant-2/2", "tenant-3/3"]
+    );
+}
+
+#[test]
+fn task_local_set() {
+    setup_tracing();
+
+    inel::block_on(async {
+        REQUEST.set(1);
+
+        let child = inel::spawn(async {
+            assert!(REQUEST.try_with(|_| ()).is_err());
+            REQUEST.set(2);
+            sleep(millis(1)).await;
+            REQUEST.get()
+        });
+
+        sleep(millis(2)).await;
+        assert_eq!(REQUEST.get(), 1);
+        assert_eq!(child.join().await, Some(2));
+
+        REQUEST.sync_scope(3, || assert_eq!(REQUEST.get(), 3));
+        assert_eq!(REQUEST.take(), Some(1));
+    });
+
+    assert!(REQUEST.try_with(|_| ()).is_err());
+}
+
+#[test]
+fn yield_to_others() {
+    setup_tracing();
+
+    let order = inel::block_on(async {
+        let order = Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let other = order.clone();
+        let handle = inel::spawn(async move {
+            other.borrow_mut().push("other");
+        });
+
+        order.borrow_mut().push("before");
+        yield_now().await;
+        order.borrow_mut().push("after");
+
+        handle.join().await.unwrap();
+        order.take()
+    });
+
+    assert_eq!(order, vec!["before", "other", "after"]);
+}
+
+#[test]
+fn priority() {
+    setup_tracing();
+
+    let order = inel::block_on(async {
+        let order = Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let handles = [Priority::Background, Priority::Normal, Priority::High].map(|priority| {
+            let order = order.clone();
+            inel::spawn_with_priority(priority, async move {
+                order.borrow_mut().push(priority);
+            })
+        });
+
+        assert_eq!(inel::metrics().executor.queued_with(Priority::High), 1);
+
+        for handle in handles {
+            handle.join().await.unwrap();
+        }
+
+        order.take()
+    });
+
+    assert_eq!(
+        order,
+        vec![Priority::High, Priority::Normal, Priority::Background]
+    );
+}